@@ -0,0 +1,422 @@
+use crate::backend::providers::ProviderConfig;
+use crate::context::{get_active_window_info, WindowInfo};
+use crate::context_policy::PASSWORD_MANAGERS;
+use crate::store::{JsonStore, Validate};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tracing::{debug, info, warn};
+
+const APP_PROFILES_FILE: &str = "app_profiles.json";
+
+/// How dictated text gets into the target app - matches TypeScript interface
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InsertionMethod {
+    /// Simulated typing, works everywhere but slow for long text
+    #[default]
+    Type,
+    /// Put the text on the clipboard and press paste, then restore the clipboard
+    Paste,
+}
+
+/// Which windows a profile applies to - matches TypeScript interface.
+/// Every given pattern must match; patterns ignore case and may use `*` wildcards.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppMatcher {
+    /// e.g. `com.apple.mail` or `com.jetbrains.*`
+    pub bundle_id: Option<String>,
+    pub process_name: Option<String>,
+    /// e.g. `*Gmail*`
+    pub window_title: Option<String>,
+}
+
+/// Settings that apply while dictating into matching apps - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppProfile {
+    pub name: String,
+    #[serde(rename = "match")]
+    pub matcher: AppMatcher,
+    /// When false, recordings made in these apps are not processed
+    #[serde(default = "default_dictation_allowed")]
+    pub dictation_allowed: bool,
+    /// Backend profile to use instead of the active one
+    #[serde(default)]
+    pub backend_profile: Option<String>,
+    /// Speech-to-text provider to use instead of the one chosen in the app
+    #[serde(default)]
+    pub provider: Option<ProviderConfig>,
+    /// Language or locale such as `de-DE`
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Text pipeline to run instead of the active one
+    #[serde(default)]
+    pub pipeline: Option<String>,
+    #[serde(default)]
+    pub insertion: Option<InsertionMethod>,
+}
+
+fn default_dictation_allowed() -> bool {
+    true
+}
+
+/// All app profiles, checked in order - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppProfileSettings {
+    pub profiles: Vec<AppProfile>,
+}
+
+impl Default for AppProfileSettings {
+    fn default() -> Self {
+        let password_manager = |&(name, bundle_id): &(&str, &str)| AppProfile {
+            name: name.to_string(),
+            matcher: AppMatcher {
+                bundle_id: Some(bundle_id.to_string()),
+                ..AppMatcher::default()
+            },
+            dictation_allowed: false,
+            backend_profile: None,
+            provider: None,
+            language: None,
+            pipeline: None,
+            insertion: None,
+        };
+        Self {
+            profiles: PASSWORD_MANAGERS.iter().map(password_manager).collect(),
+        }
+    }
+}
+
+/// Case-insensitive regex for a pattern where `*` matches anything
+fn glob(pattern: &str) -> Result<Regex, String> {
+    let pattern = pattern
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*");
+    RegexBuilder::new(&format!("^{}$", pattern))
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn matches(pattern: &Option<String>, value: &str) -> bool {
+    match pattern {
+        Some(pattern) => glob(pattern).is_ok_and(|glob| glob.is_match(value)),
+        None => true,
+    }
+}
+
+impl AppMatcher {
+    fn is_empty(&self) -> bool {
+        self.bundle_id.is_none() && self.process_name.is_none() && self.window_title.is_none()
+    }
+
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        !self.is_empty()
+            && matches(&self.bundle_id, &window.bundle_id)
+            && matches(&self.process_name, &window.process_name)
+            && matches(&self.window_title, &window.window_title)
+    }
+}
+
+impl AppProfileSettings {
+    /// First profile matching the window
+    pub fn matching(&self, window: &WindowInfo) -> Option<&AppProfile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.matcher.matches(window))
+    }
+}
+
+impl Validate for AppProfileSettings {
+    fn validate(&self) -> Result<(), String> {
+        for profile in &self.profiles {
+            if profile.matcher.is_empty() {
+                return Err(format!(
+                    "App profile '{}' needs a bundle id, process name or window title",
+                    profile.name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// App profiles persisted as JSON in the app data directory
+#[derive(Debug, Default)]
+pub struct AppProfileStore {
+    store: JsonStore<AppProfileSettings>,
+}
+
+impl AppProfileStore {
+    /// Load saved profiles from the given app data directory, falling back to defaults
+    pub fn load(app_dir: PathBuf) -> Self {
+        let store = JsonStore::<AppProfileSettings>::load(app_dir.join(APP_PROFILES_FILE));
+        info!("Loaded {} app profiles", store.get().profiles.len());
+        Self { store }
+    }
+
+    pub fn settings(&self) -> &AppProfileSettings {
+        self.store.get()
+    }
+
+    /// Replace all profiles and write them to disk
+    pub fn set_settings(&mut self, settings: AppProfileSettings) -> Result<(), String> {
+        self.store.set(settings)
+    }
+}
+
+fn is_known(bundle_id: &str) -> bool {
+    !bundle_id.is_empty() && bundle_id != "unknown"
+}
+
+/// The window a recording was made in. The frontmost window only fills in the title
+/// and process when it still belongs to the recording's app.
+fn recording_window(package_name: &str, frontmost: Option<WindowInfo>) -> WindowInfo {
+    match frontmost {
+        Some(window)
+            if is_known(&window.bundle_id)
+                && (!is_known(package_name) || window.bundle_id == package_name) =>
+        {
+            window
+        }
+        _ => WindowInfo {
+            window_title: String::new(),
+            application_name: String::new(),
+            bundle_id: package_name.to_string(),
+            process_name: String::new(),
+        },
+    }
+}
+
+/// The window for a recording made in `package_name`, or the frontmost window when
+/// the app isn't known
+pub fn active_window(package_name: &str) -> WindowInfo {
+    recording_window(package_name, get_active_window_info().ok())
+}
+
+/// Profile with the given name, for work resumed after the profile was resolved
+pub fn named(app: &AppHandle, name: &str) -> Option<AppProfile> {
+    let store = app.try_state::<Mutex<AppProfileStore>>()?;
+    let store = match store.lock() {
+        Ok(store) => store,
+        Err(e) => {
+            warn!("Failed to lock app profiles: {}", e);
+            return None;
+        }
+    };
+    let profile = store
+        .settings()
+        .profiles
+        .iter()
+        .find(|profile| profile.name == name)
+        .cloned();
+    if profile.is_none() {
+        warn!("App profile '{}' no longer exists", name);
+    }
+    profile
+}
+
+/// Profile for the app a recording was made in, given by the context's package name.
+/// An empty package name means the frontmost app.
+pub fn resolve_for_app(app: &AppHandle, package_name: &str) -> Option<AppProfile> {
+    let store = app.try_state::<Mutex<AppProfileStore>>()?;
+    let window = active_window(package_name);
+    let store = match store.lock() {
+        Ok(store) => store,
+        Err(e) => {
+            warn!("Failed to lock app profiles: {}", e);
+            return None;
+        }
+    };
+    let profile = store.settings().matching(&window).cloned();
+    if let Some(profile) = &profile {
+        debug!(
+            "App profile '{}' applies to {} ({})",
+            profile.name, window.bundle_id, window.window_title
+        );
+    }
+    profile
+}
+
+/// Get all app profiles
+#[tauri::command]
+pub async fn get_app_profiles(
+    store: State<'_, Mutex<AppProfileStore>>,
+) -> Result<AppProfileSettings, String> {
+    let store = store.lock().map_err(|e| e.to_string())?;
+    Ok(store.settings().clone())
+}
+
+/// Replace and save all app profiles
+#[tauri::command]
+pub async fn set_app_profiles(
+    settings: AppProfileSettings,
+    store: State<'_, Mutex<AppProfileStore>>,
+) -> Result<(), String> {
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    store.set_settings(settings)
+}
+
+/// The profile that applies to the app with the given package name, or to the
+/// frontmost app when none is given
+#[tauri::command]
+pub async fn get_active_app_profile(
+    app: AppHandle,
+    package_name: Option<String>,
+) -> Result<Option<AppProfile>, String> {
+    Ok(resolve_for_app(
+        &app,
+        package_name.as_deref().unwrap_or_default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(bundle_id: &str, process_name: &str, window_title: &str) -> WindowInfo {
+        WindowInfo {
+            window_title: window_title.to_string(),
+            application_name: process_name.to_string(),
+            bundle_id: bundle_id.to_string(),
+            process_name: process_name.to_string(),
+        }
+    }
+
+    fn profile(name: &str, matcher: AppMatcher) -> AppProfile {
+        AppProfile {
+            name: name.to_string(),
+            matcher,
+            dictation_allowed: true,
+            backend_profile: None,
+            provider: None,
+            language: None,
+            pipeline: None,
+            insertion: None,
+        }
+    }
+
+    #[test]
+    fn first_matching_profile_wins() {
+        let settings = AppProfileSettings {
+            profiles: vec![
+                profile(
+                    "gmail",
+                    AppMatcher {
+                        process_name: Some("Google Chrome".to_string()),
+                        window_title: Some("*gmail*".to_string()),
+                        ..AppMatcher::default()
+                    },
+                ),
+                profile(
+                    "editors",
+                    AppMatcher {
+                        bundle_id: Some("com.jetbrains.*".to_string()),
+                        ..AppMatcher::default()
+                    },
+                ),
+                profile(
+                    "mail",
+                    AppMatcher {
+                        bundle_id: Some("com.apple.mail".to_string()),
+                        ..AppMatcher::default()
+                    },
+                ),
+            ],
+        };
+
+        let name = |window: &WindowInfo| settings.matching(window).map(|p| p.name.as_str());
+        assert_eq!(
+            name(&window(
+                "com.google.Chrome",
+                "Google Chrome",
+                "Inbox - Gmail"
+            )),
+            Some("gmail")
+        );
+        assert_eq!(
+            name(&window("com.google.Chrome", "Google Chrome", "Docs")),
+            None
+        );
+        assert_eq!(
+            name(&window("com.jetbrains.intellij", "idea", "Main.kt")),
+            Some("editors")
+        );
+        assert_eq!(name(&window("COM.APPLE.MAIL", "Mail", "")), Some("mail"));
+        assert_eq!(name(&window("com.apple.mailer", "Mailer", "")), None);
+    }
+
+    #[test]
+    fn password_managers_are_blocked_by_default() {
+        let settings = AppProfileSettings::default();
+        for bundle_id in [
+            "com.1password.1password",
+            "com.agilebits.onepassword7",
+            "com.apple.Passwords",
+        ] {
+            let profile = settings.matching(&window(bundle_id, "", "Vault")).unwrap();
+            assert!(!profile.dictation_allowed, "{}", bundle_id);
+        }
+    }
+
+    #[test]
+    fn profiles_need_a_pattern() {
+        let settings = AppProfileSettings {
+            profiles: vec![profile("everything", AppMatcher::default())],
+        };
+        assert!(settings.validate().is_err());
+        assert!(settings
+            .matching(&window("com.apple.mail", "Mail", ""))
+            .is_none());
+    }
+
+    #[test]
+    fn parses_profile_json() {
+        let profile: AppProfile = serde_json::from_str(
+            r#"{
+                "name": "Mail",
+                "match": {"bundleId": "com.apple.mail"},
+                "pipeline": "formal",
+                "insertion": "paste",
+                "language": "en-GB"
+            }"#,
+        )
+        .unwrap();
+        assert!(profile.dictation_allowed);
+        assert_eq!(profile.insertion, Some(InsertionMethod::Paste));
+        assert_eq!(profile.pipeline.as_deref(), Some("formal"));
+    }
+
+    #[test]
+    fn recording_window_comes_from_the_context() {
+        let chrome = window("com.google.Chrome", "Google Chrome", "Inbox - Gmail");
+
+        let same = recording_window("com.google.Chrome", Some(chrome.clone()));
+        assert_eq!(same.window_title, "Inbox - Gmail");
+
+        // Focus moved on after the recording
+        let moved = recording_window("com.apple.mail", Some(chrome.clone()));
+        assert_eq!(moved.bundle_id, "com.apple.mail");
+        assert!(moved.window_title.is_empty());
+
+        assert_eq!(
+            recording_window("", Some(chrome)).bundle_id,
+            "com.google.Chrome"
+        );
+        assert_eq!(
+            recording_window("com.apple.mail", None).bundle_id,
+            "com.apple.mail"
+        );
+        assert_eq!(
+            recording_window("unknown", Some(window("unknown", "", ""))).bundle_id,
+            "unknown"
+        );
+    }
+}
@@ -2,7 +2,10 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use tauri::{AppHandle, Manager, Emitter};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use crate::backend::http::app_client;
+use crate::backend::tokens::TokenSource;
 use tiny_http::{Server, Response, Header};
 use url::Url;
 
@@ -296,4 +299,126 @@ pub async fn handle_deep_link_with_app(app: AppHandle, url: String) -> Result<()
     
     // Also handle via polling mechanism
     handle_deep_link(url).await
-} 
\ No newline at end of file
+} 
+/// Auth0 tenant used for refreshing tokens - must match the config in auth0.ts
+const AUTH0_DOMAIN: &str = "dev-v6bfenyhz8m15z6j.eu.auth0.com";
+const AUTH0_CLIENT_ID: &str = "Nobjj5cwIKiVfUP2iSfIVVRuouNUqlno";
+const TOKEN_FILE: &str = "auth_tokens.json";
+
+lazy_static::lazy_static! {
+    /// Serializes refreshes so concurrent 401s only use the refresh token once
+    static ref REFRESH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// Tokens as stored by the frontend in auth_tokens.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    /// id_token, expires_in and anything else the frontend keeps
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+fn token_file(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let app_dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(app_dir.join(TOKEN_FILE))
+}
+
+/// Read the stored tokens, if the user is signed in
+pub fn read_stored_tokens(app: &AppHandle) -> Option<StoredTokens> {
+    let content = std::fs::read_to_string(token_file(app).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Exchange the stored refresh token for a new access token and save it
+pub async fn refresh_stored_tokens(app: &AppHandle) -> Result<StoredTokens, String> {
+    let mut tokens = read_stored_tokens(app).ok_or("Not signed in")?;
+    let refresh_token = tokens.refresh_token.clone().ok_or("No refresh token stored")?;
+
+    let response = app_client(app)
+        .post(format!("https://{}/oauth/token", AUTH0_DOMAIN))
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("client_id", AUTH0_CLIENT_ID),
+            ("refresh_token", refresh_token.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Token refresh request failed: {}", e))?;
+
+    let status = response.status();
+    let body: Value = response.json().await
+        .map_err(|e| format!("Invalid token refresh response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("Token refresh failed ({}): {}", status,
+            body.get("error_description").or(body.get("error")).unwrap_or(&body)));
+    }
+
+    let Value::Object(fresh) = body else {
+        return Err("Invalid token refresh response".to_string());
+    };
+    // Auth0 only returns a new refresh token when rotation is enabled, so keep the old one otherwise
+    for (key, value) in fresh {
+        match key.as_str() {
+            "access_token" => tokens.access_token = value.as_str().unwrap_or_default().to_string(),
+            "refresh_token" => tokens.refresh_token = value.as_str().map(str::to_string),
+            _ => { tokens.extra.insert(key, value); }
+        }
+    }
+    if tokens.access_token.is_empty() {
+        return Err("Token refresh response has no access token".to_string());
+    }
+
+    let content = serde_json::to_string(&tokens).map_err(|e| e.to_string())?;
+    std::fs::write(token_file(app)?, content)
+        .map_err(|e| format!("Failed to store tokens: {}", e))?;
+    println!("Access token refreshed");
+    Ok(tokens)
+}
+
+/// Backend token source backed by auth_tokens.json
+pub struct StoredTokenSource {
+    app: AppHandle,
+}
+
+impl StoredTokenSource {
+    pub fn new(app: AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for StoredTokenSource {
+    fn access_token(&self) -> Option<String> {
+        read_stored_tokens(&self.app).map(|tokens| tokens.access_token)
+    }
+
+    async fn refresh(&self, rejected: Option<&str>) -> Option<String> {
+        let _guard = REFRESH_LOCK.lock().await;
+
+        // Another request may have refreshed while we waited
+        if let Some(current) = self.access_token() {
+            if Some(current.as_str()) != rejected {
+                return Some(current);
+            }
+        }
+
+        match refresh_stored_tokens(&self.app).await {
+            Ok(tokens) => {
+                if let Err(e) = self.app.emit("auth-tokens-refreshed", ()) {
+                    eprintln!("Failed to emit auth-tokens-refreshed event: {}", e);
+                }
+                Some(tokens.access_token)
+            }
+            Err(e) => {
+                eprintln!("Failed to refresh access token: {}", e);
+                if let Err(e) = self.app.emit("auth-required", &e) {
+                    eprintln!("Failed to emit auth-required event: {}", e);
+                }
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use super::retry::RetryPolicy;
+use super::upload::UploadFormat;
+use crate::store::{JsonStore, Validate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+const BACKEND_SETTINGS_FILE: &str = "backend_settings.json";
+
+/// Production endpoint used when nothing else is configured
+pub const DEFAULT_BACKEND_URL: &str = "https://process-voice.whisperme.app";
+
+const DEFAULT_PROFILE_NAME: &str = "production";
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Overrides the selected profile's base URL
+const ENV_BACKEND_URL: &str = "WHISPERME_BACKEND_URL";
+/// Overrides the selected profile's request timeout, in seconds
+const ENV_BACKEND_TIMEOUT_SECS: &str = "WHISPERME_BACKEND_TIMEOUT_SECS";
+/// Overrides which profile is selected
+const ENV_BACKEND_PROFILE: &str = "WHISPERME_BACKEND_PROFILE";
+/// Extra headers as `Name: value` pairs separated by newlines or semicolons
+const ENV_BACKEND_HEADERS: &str = "WHISPERME_BACKEND_HEADERS";
+
+/// A named backend endpoint - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendProfile {
+    pub name: String,
+    pub base_url: String,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Extra headers sent with every request to this endpoint
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub upload_format: UploadFormat,
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+impl BackendProfile {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// Build a URL for a path relative to the base URL
+    pub fn url(&self, path: &str) -> String {
+        let base = self.base_url.trim_end_matches('/');
+        let path = path.trim_start_matches('/');
+        if path.is_empty() {
+            base.to_string()
+        } else {
+            format!("{}/{}", base, path)
+        }
+    }
+
+    /// Attach this profile's timeout and extra headers to a request
+    pub fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        self.headers
+            .iter()
+            .fold(request.timeout(self.timeout()), |request, (name, value)| {
+                request.header(name, value)
+            })
+    }
+}
+
+impl Default for BackendProfile {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PROFILE_NAME.to_string(),
+            base_url: DEFAULT_BACKEND_URL.to_string(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            headers: BTreeMap::new(),
+            retry: RetryPolicy::default(),
+            upload_format: UploadFormat::default(),
+        }
+    }
+}
+
+/// All configured endpoint profiles and which one is active - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendSettings {
+    pub active_profile: String,
+    pub profiles: Vec<BackendProfile>,
+}
+
+impl Default for BackendSettings {
+    fn default() -> Self {
+        Self {
+            active_profile: DEFAULT_PROFILE_NAME.to_string(),
+            profiles: vec![BackendProfile::default()],
+        }
+    }
+}
+
+/// Check that a header can be sent as configured
+fn check_header(name: &str, value: &str) -> Result<(), String> {
+    reqwest::header::HeaderName::from_bytes(name.as_bytes())
+        .map_err(|e| format!("Invalid header name '{}': {}", name, e))?;
+    reqwest::header::HeaderValue::from_str(value)
+        .map_err(|e| format!("Invalid value for header '{}': {}", name, e))?;
+    Ok(())
+}
+
+impl Validate for BackendSettings {
+    fn validate(&self) -> Result<(), String> {
+        if self.profiles.is_empty() {
+            return Err("At least one backend profile is required".to_string());
+        }
+        for profile in &self.profiles {
+            url::Url::parse(&profile.base_url)
+                .map_err(|e| format!("Invalid base URL for profile '{}': {}", profile.name, e))?;
+            for (name, value) in &profile.headers {
+                check_header(name, value)
+                    .map_err(|e| format!("{} in profile '{}'", e, profile.name))?;
+            }
+        }
+        if !self.profiles.iter().any(|p| p.name == self.active_profile) {
+            return Err(format!("Unknown backend profile '{}'", self.active_profile));
+        }
+        Ok(())
+    }
+}
+
+/// Backend endpoint settings persisted as JSON in the app data directory
+#[derive(Debug, Default)]
+pub struct BackendConfigStore {
+    store: JsonStore<BackendSettings>,
+}
+
+impl BackendConfigStore {
+    /// Load saved settings from the given app data directory, falling back to defaults
+    pub fn load(app_dir: PathBuf) -> Self {
+        let store = JsonStore::<BackendSettings>::load(app_dir.join(BACKEND_SETTINGS_FILE));
+        info!(
+            "Loaded {} backend profiles, active: {}",
+            store.get().profiles.len(),
+            store.get().active_profile
+        );
+        Self { store }
+    }
+
+    pub fn settings(&self) -> &BackendSettings {
+        self.store.get()
+    }
+
+    /// Replace all profiles and write them to disk
+    pub fn set_settings(&mut self, settings: BackendSettings) -> Result<(), String> {
+        self.store.set(settings)
+    }
+
+    /// Switch the active profile by name
+    pub fn select_profile(&mut self, name: &str) -> Result<(), String> {
+        let settings = BackendSettings {
+            active_profile: name.to_string(),
+            ..self.settings().clone()
+        };
+        self.store.set(settings)?;
+        info!("Switched backend profile to '{}'", name);
+        Ok(())
+    }
+
+    /// The active profile with environment-variable overrides applied
+    pub fn active_profile(&self) -> BackendProfile {
+        self.active_profile_with(|name| std::env::var(name).ok())
+    }
+
+    /// The active profile with overrides from variables looked up with `var`
+    fn active_profile_with(&self, var: impl Fn(&str) -> Option<String>) -> BackendProfile {
+        let name =
+            var(ENV_BACKEND_PROFILE).unwrap_or_else(|| self.settings().active_profile.clone());
+
+        let mut profile = self
+            .settings()
+            .profiles
+            .iter()
+            .find(|p| p.name == name)
+            .or_else(|| {
+                warn!(
+                    "Backend profile '{}' not found, using the first profile",
+                    name
+                );
+                self.settings().profiles.first()
+            })
+            .cloned()
+            .unwrap_or_default();
+
+        apply_overrides(&mut profile, var);
+        profile
+    }
+
+    /// A profile by name with environment-variable overrides applied
+    pub fn profile(&self, name: &str) -> Option<BackendProfile> {
+        let mut profile = self
+            .settings()
+            .profiles
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()?;
+        apply_env_overrides(&mut profile);
+        Some(profile)
+    }
+
+    /// Save the upload format detected for a profile that was left on `Auto`
+    pub fn remember_upload_format(
+        &mut self,
+        name: &str,
+        format: UploadFormat,
+    ) -> Result<(), String> {
+        let mut settings = self.settings().clone();
+        let Some(profile) = settings
+            .profiles
+            .iter_mut()
+            .find(|p| p.name == name && p.upload_format == UploadFormat::Auto)
+        else {
+            return Ok(());
+        };
+        profile.upload_format = format;
+        info!("Backend profile '{}' now uploads as {:?}", name, format);
+        self.store.set(settings)
+    }
+}
+
+fn apply_env_overrides(profile: &mut BackendProfile) {
+    apply_overrides(profile, |name| std::env::var(name).ok());
+}
+
+/// Apply overrides from variables looked up with `var`
+fn apply_overrides(profile: &mut BackendProfile, var: impl Fn(&str) -> Option<String>) {
+    if let Some(url) = var(ENV_BACKEND_URL) {
+        debug!("Backend URL overridden by {}", ENV_BACKEND_URL);
+        profile.base_url = url;
+    }
+
+    if let Some(timeout) = var(ENV_BACKEND_TIMEOUT_SECS) {
+        match timeout.parse() {
+            Ok(secs) => profile.timeout_secs = secs,
+            Err(_) => warn!("Ignoring invalid {}: {}", ENV_BACKEND_TIMEOUT_SECS, timeout),
+        }
+    }
+
+    if let Some(headers) = var(ENV_BACKEND_HEADERS) {
+        for pair in headers.split(['\n', ';']).filter(|p| !p.trim().is_empty()) {
+            let header = pair
+                .split_once(':')
+                .map(|(name, value)| (name.trim(), value.trim()))
+                .filter(|(name, value)| check_header(name, value).is_ok());
+            match header {
+                Some((name, value)) => {
+                    profile.headers.insert(name.to_string(), value.to_string());
+                }
+                None => warn!(
+                    "Ignoring malformed header in {}: {}",
+                    ENV_BACKEND_HEADERS, pair
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, base_url: &str) -> BackendProfile {
+        BackendProfile {
+            name: name.to_string(),
+            base_url: base_url.to_string(),
+            ..BackendProfile::default()
+        }
+    }
+
+    fn settings(active: &str, profiles: Vec<BackendProfile>) -> BackendSettings {
+        BackendSettings {
+            active_profile: active.to_string(),
+            profiles,
+        }
+    }
+
+    /// Look up variables in a fixed list instead of the environment
+    fn vars<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    /// The default profile after applying the given variables
+    fn overridden(overrides: &[(&str, &str)]) -> BackendProfile {
+        let mut profile = BackendProfile::default();
+        apply_overrides(&mut profile, vars(overrides));
+        profile
+    }
+
+    #[test]
+    fn joins_paths_to_the_base_url() {
+        let staging = profile("staging", "https://staging.example.com/api/voice/");
+        assert_eq!(
+            staging.url("/health"),
+            "https://staging.example.com/api/voice/health"
+        );
+        assert_eq!(
+            staging.url("jobs/1"),
+            "https://staging.example.com/api/voice/jobs/1"
+        );
+        assert_eq!(staging.url(""), "https://staging.example.com/api/voice");
+    }
+
+    #[test]
+    fn validates_settings() {
+        assert!(BackendSettings::default().validate().is_ok());
+        assert!(settings("production", Vec::new()).validate().is_err());
+        assert!(settings("local", vec![BackendProfile::default()])
+            .validate()
+            .unwrap_err()
+            .contains("Unknown backend profile"));
+        assert!(settings("local", vec![profile("local", "localhost 3000")])
+            .validate()
+            .unwrap_err()
+            .contains("Invalid base URL"));
+
+        for (name, value) in [("X-Team", "voice\nteam"), ("Bad Header", "1")] {
+            let mut with_header = BackendProfile::default();
+            with_header
+                .headers
+                .insert(name.to_string(), value.to_string());
+            let error = settings(DEFAULT_PROFILE_NAME, vec![with_header])
+                .validate()
+                .unwrap_err();
+            assert!(error.contains(name), "{}", error);
+        }
+    }
+
+    #[test]
+    fn selects_only_known_profiles() {
+        let mut store = BackendConfigStore::default();
+        store
+            .set_settings(settings(
+                DEFAULT_PROFILE_NAME,
+                vec![
+                    BackendProfile::default(),
+                    profile("local", "http://localhost:3000/api/voice"),
+                ],
+            ))
+            .unwrap();
+
+        store.select_profile("local").unwrap();
+        assert_eq!(store.settings().active_profile, "local");
+        assert!(store.select_profile("missing").is_err());
+        assert_eq!(store.settings().active_profile, "local");
+        assert_eq!(store.profile("local").unwrap().name, "local");
+        assert!(store.profile("missing").is_none());
+
+        let active =
+            store.active_profile_with(vars(&[(ENV_BACKEND_PROFILE, DEFAULT_PROFILE_NAME)]));
+        assert_eq!(active.name, DEFAULT_PROFILE_NAME);
+        let active = store.active_profile_with(vars(&[(ENV_BACKEND_PROFILE, "missing")]));
+        assert_eq!(
+            active.name, DEFAULT_PROFILE_NAME,
+            "falls back to the first profile"
+        );
+    }
+
+    #[test]
+    fn applies_environment_overrides() {
+        assert_eq!(overridden(&[]), BackendProfile::default());
+
+        let profile = overridden(&[(ENV_BACKEND_URL, "http://localhost:3000/api/voice")]);
+        assert_eq!(profile.base_url, "http://localhost:3000/api/voice");
+
+        assert_eq!(
+            overridden(&[(ENV_BACKEND_TIMEOUT_SECS, "5")]).timeout_secs,
+            5
+        );
+        assert_eq!(
+            overridden(&[(ENV_BACKEND_TIMEOUT_SECS, "soon")]).timeout_secs,
+            DEFAULT_TIMEOUT_SECS
+        );
+
+        let profile = overridden(&[(
+            ENV_BACKEND_HEADERS,
+            "X-Team: voice; X-Trace:abc\nno separator;Bad Header: 1;X-Bad: a\rb",
+        )]);
+        assert_eq!(
+            profile.headers,
+            BTreeMap::from([
+                ("X-Team".to_string(), "voice".to_string()),
+                ("X-Trace".to_string(), "abc".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn remembers_detected_upload_format_once() {
+        let mut store = BackendConfigStore::default();
+        store
+            .remember_upload_format(DEFAULT_PROFILE_NAME, UploadFormat::Json)
+            .unwrap();
+        // A format that is no longer `Auto` is left alone
+        store
+            .remember_upload_format(DEFAULT_PROFILE_NAME, UploadFormat::Multipart)
+            .unwrap();
+        store
+            .remember_upload_format("unknown", UploadFormat::Json)
+            .unwrap();
+
+        assert_eq!(
+            store.settings().profiles[0].upload_format,
+            UploadFormat::Json
+        );
+    }
+}
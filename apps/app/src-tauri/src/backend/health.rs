@@ -0,0 +1,386 @@
+use super::config::BackendProfile;
+use super::tokens::TokenSource;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Health and capabilities endpoint, relative to the profile's base URL
+const HEALTH_PATH: &str = "health";
+/// Major version of the backend API this app speaks
+const SUPPORTED_API_MAJOR: u64 = 1;
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of a backend health check - matches TypeScript interface
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendHealth {
+    pub reachable: bool,
+    /// Round trip of the health request
+    pub latency_ms: Option<u64>,
+    pub server_version: Option<String>,
+    /// Audio formats the server accepts, e.g. `wav`, `webm`
+    pub audio_formats: Vec<String>,
+    pub max_upload_bytes: Option<u64>,
+    /// Whether the stored access token was accepted; `None` when unknown
+    pub auth_valid: Option<bool>,
+    /// False when the server speaks an API version this app doesn't support
+    pub compatible: bool,
+    /// What is wrong, if anything
+    pub error: Option<String>,
+}
+
+impl BackendHealth {
+    fn unreachable(error: String) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::default()
+        }
+    }
+
+    /// Whether recordings can be sent to this backend
+    pub fn is_usable(&self) -> bool {
+        self.reachable && self.compatible && self.error.is_none()
+    }
+}
+
+/// Body of the health endpoint; every field is optional so partial answers still work
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct HealthResponse {
+    #[serde(alias = "serverVersion")]
+    version: Option<String>,
+    #[serde(alias = "supportedFormats")]
+    audio_formats: Vec<String>,
+    #[serde(alias = "maxUploadSize")]
+    max_upload_bytes: Option<u64>,
+    #[serde(alias = "authValid")]
+    authenticated: Option<bool>,
+}
+
+/// Major version of a `1.2.3`-style version string
+fn major_version(version: &str) -> Option<u64> {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split(['.', '-', '+'])
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Explain why a server version can't be used, if it can't
+fn version_error(version: &str) -> Option<String> {
+    match major_version(version) {
+        Some(SUPPORTED_API_MAJOR) => None,
+        Some(major) if major > SUPPORTED_API_MAJOR => Some(format!(
+            "Backend version {} is newer than this app supports ({}.x), please update the app",
+            version, SUPPORTED_API_MAJOR
+        )),
+        Some(_) => Some(format!(
+            "Backend version {} is too old, this app needs {}.x",
+            version, SUPPORTED_API_MAJOR
+        )),
+        None => {
+            warn!("Unrecognised backend version '{}'", version);
+            None
+        }
+    }
+}
+
+fn is_auth_failure(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN
+}
+
+/// Request the health endpoint, returning the response and its round trip
+async fn request_health(
+    client: &reqwest::Client,
+    profile: &BackendProfile,
+    token: Option<&str>,
+) -> Result<(reqwest::Response, u64), BackendHealth> {
+    let mut request = profile
+        .apply(client.get(profile.url(HEALTH_PATH)))
+        .timeout(HEALTH_TIMEOUT);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    let started = Instant::now();
+    match request.send().await {
+        Ok(response) => Ok((response, started.elapsed().as_millis() as u64)),
+        Err(e) => {
+            warn!("Backend health check failed: {}", e);
+            Err(BackendHealth::unreachable(format!(
+                "Backend not reachable: {}",
+                e
+            )))
+        }
+    }
+}
+
+/// Check reachability, version, capabilities and auth of the profile's backend.
+/// A rejected access token is refreshed once before auth counts as failed.
+pub async fn check_backend_health(
+    client: &reqwest::Client,
+    profile: &BackendProfile,
+    tokens: Option<&dyn TokenSource>,
+) -> BackendHealth {
+    info!("Checking backend health for profile '{}'", profile.name);
+
+    let mut token = tokens.and_then(|tokens| tokens.access_token());
+    let (mut response, mut latency_ms) =
+        match request_health(client, profile, token.as_deref()).await {
+            Ok(answer) => answer,
+            Err(health) => return health,
+        };
+    if let Some(tokens) = tokens.filter(|_| is_auth_failure(response.status())) {
+        info!("Backend rejected the access token, refreshing");
+        if let Some(fresh) = tokens.refresh(token.as_deref()).await {
+            (response, latency_ms) = match request_health(client, profile, Some(&fresh)).await {
+                Ok(answer) => answer,
+                Err(health) => return health,
+            };
+            token = Some(fresh);
+        }
+    }
+    let status = response.status();
+    debug!("Backend health response: {} in {}ms", status, latency_ms);
+
+    let mut health = BackendHealth {
+        reachable: true,
+        latency_ms: Some(latency_ms),
+        compatible: true,
+        ..BackendHealth::default()
+    };
+
+    if status == reqwest::StatusCode::NOT_FOUND {
+        // Servers from before the health endpoint; make sure the URL points at one
+        health.error = check_legacy_endpoint(client, profile).await;
+        return health;
+    }
+    if is_auth_failure(status) {
+        health.auth_valid = Some(false);
+        health.error = Some("The backend rejected the stored sign-in, please sign in again".into());
+        return health;
+    }
+    if !status.is_success() {
+        health.error = Some(format!("Backend health check failed with {}", status));
+        return health;
+    }
+
+    let body: HealthResponse = match response.json().await {
+        Ok(body) => body,
+        Err(e) => {
+            health.error = Some(format!("Invalid health response: {}", e));
+            return health;
+        }
+    };
+
+    if let Some(version) = &body.version {
+        if let Some(error) = version_error(version) {
+            health.compatible = false;
+            health.error = Some(error);
+        }
+    }
+    health.server_version = body.version;
+    health.audio_formats = body.audio_formats;
+    health.max_upload_bytes = body.max_upload_bytes;
+    health.auth_valid = body.authenticated.filter(|_| token.is_some());
+    health
+}
+
+/// Tell an older server apart from a wrong URL; older servers answer GET with 405 or 2xx
+async fn check_legacy_endpoint(
+    client: &reqwest::Client,
+    profile: &BackendProfile,
+) -> Option<String> {
+    match profile
+        .apply(client.get(profile.url("")))
+        .timeout(HEALTH_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(response) => {
+            let status = response.status();
+            if status == reqwest::StatusCode::METHOD_NOT_ALLOWED || status.is_success() {
+                debug!("Backend has no health endpoint, assuming an older server");
+                None
+            } else if status == reqwest::StatusCode::NOT_FOUND {
+                Some(format!(
+                    "No voice processing endpoint at {}",
+                    profile.base_url
+                ))
+            } else {
+                Some(format!(
+                    "Backend has no health endpoint and answered {} at {}",
+                    status, profile.base_url
+                ))
+            }
+        }
+        Err(e) => Some(format!("Backend not reachable: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{MockBackend, MockResponse};
+    use std::sync::Mutex;
+
+    /// Token source whose stored token has expired; `refreshed` is what a refresh yields
+    struct ExpiredTokens {
+        current: Mutex<String>,
+        refreshed: Option<&'static str>,
+    }
+
+    #[async_trait::async_trait]
+    impl TokenSource for ExpiredTokens {
+        fn access_token(&self) -> Option<String> {
+            Some(self.current.lock().unwrap().clone())
+        }
+
+        async fn refresh(&self, _rejected: Option<&str>) -> Option<String> {
+            let fresh = self.refreshed?.to_string();
+            *self.current.lock().unwrap() = fresh.clone();
+            Some(fresh)
+        }
+    }
+
+    #[test]
+    fn accepts_only_the_supported_major_version() {
+        assert_eq!(version_error("1.4.2"), None);
+        assert_eq!(version_error("v1.0.0-beta.1"), None);
+        assert!(version_error("2.0.0").unwrap().contains("update the app"));
+        assert!(version_error("0.9.1").unwrap().contains("too old"));
+        assert_eq!(version_error("nightly"), None);
+    }
+
+    #[tokio::test]
+    async fn reports_server_capabilities() {
+        let backend = MockBackend::start([MockResponse::Json(
+            r#"{"version": "1.3.0", "audioFormats": ["wav", "webm"], "maxUploadBytes": 26214400}"#,
+        )]);
+        let health = check_backend_health(&reqwest::Client::new(), &backend.profile(), None).await;
+
+        assert!(health.is_usable(), "{:?}", health);
+        assert!(health.latency_ms.is_some());
+        assert_eq!(health.server_version.as_deref(), Some("1.3.0"));
+        assert_eq!(health.audio_formats, vec!["wav", "webm"]);
+        assert_eq!(health.max_upload_bytes, Some(26214400));
+        assert_eq!(backend.requests()[0].path, "/api/voice/health");
+    }
+
+    #[tokio::test]
+    async fn flags_incompatible_server() {
+        let backend = MockBackend::start([MockResponse::Json(r#"{"version": "2.0.0"}"#)]);
+        let health = check_backend_health(&reqwest::Client::new(), &backend.profile(), None).await;
+
+        assert!(health.reachable);
+        assert!(!health.compatible);
+        assert!(health.error.unwrap().contains("2.0.0"));
+    }
+
+    #[tokio::test]
+    async fn tells_wrong_url_from_older_server() {
+        let wrong_url = MockBackend::start([MockResponse::Status(404), MockResponse::Status(404)]);
+        let health =
+            check_backend_health(&reqwest::Client::new(), &wrong_url.profile(), None).await;
+        assert!(health.reachable);
+        assert!(health
+            .error
+            .unwrap()
+            .starts_with("No voice processing endpoint"));
+
+        let older = MockBackend::start([MockResponse::Status(404), MockResponse::Status(405)]);
+        let health = check_backend_health(&reqwest::Client::new(), &older.profile(), None).await;
+        assert!(health.is_usable(), "{:?}", health);
+
+        let older = MockBackend::start([MockResponse::Status(404), MockResponse::Status(200)]);
+        let health = check_backend_health(&reqwest::Client::new(), &older.profile(), None).await;
+        assert!(health.is_usable(), "{:?}", health);
+    }
+
+    #[tokio::test]
+    async fn failing_endpoint_is_not_an_older_server() {
+        for status in [401, 403, 500, 502] {
+            let backend =
+                MockBackend::start([MockResponse::Status(404), MockResponse::Status(status)]);
+            let health =
+                check_backend_health(&reqwest::Client::new(), &backend.profile(), None).await;
+            assert!(!health.is_usable(), "{}: {:?}", status, health);
+            assert!(
+                health
+                    .error
+                    .as_deref()
+                    .unwrap()
+                    .contains(&status.to_string()),
+                "{:?}",
+                health.error
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_rejected_sign_in() {
+        let backend = MockBackend::start([MockResponse::Unauthorized]);
+        let health = check_backend_health(&reqwest::Client::new(), &backend.profile(), None).await;
+
+        assert_eq!(health.auth_valid, Some(false));
+        assert!(!health.is_usable());
+    }
+
+    #[tokio::test]
+    async fn refreshes_an_expired_token() {
+        let backend = MockBackend::start([
+            MockResponse::Unauthorized,
+            MockResponse::Json(r#"{"version": "1.3.0", "authenticated": true}"#),
+        ]);
+        let tokens = ExpiredTokens {
+            current: Mutex::new("expired".to_string()),
+            refreshed: Some("fresh"),
+        };
+        let health =
+            check_backend_health(&reqwest::Client::new(), &backend.profile(), Some(&tokens)).await;
+
+        assert!(health.is_usable(), "{:?}", health);
+        assert_eq!(health.auth_valid, Some(true));
+        let authorization: Vec<_> = backend
+            .requests()
+            .into_iter()
+            .map(|request| request.authorization.unwrap_or_default())
+            .collect();
+        assert_eq!(authorization, vec!["Bearer expired", "Bearer fresh"]);
+    }
+
+    #[tokio::test]
+    async fn expired_token_that_cannot_be_refreshed_is_rejected() {
+        let backend = MockBackend::start([MockResponse::Unauthorized]);
+        let tokens = ExpiredTokens {
+            current: Mutex::new("expired".to_string()),
+            refreshed: None,
+        };
+        let health =
+            check_backend_health(&reqwest::Client::new(), &backend.profile(), Some(&tokens)).await;
+
+        assert_eq!(health.auth_valid, Some(false));
+        assert!(!health.is_usable());
+        assert_eq!(backend.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unreachable_backend_is_not_usable() {
+        // Bind and release a port so nothing is listening on it
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let profile = BackendProfile {
+            base_url: format!("http://127.0.0.1:{}/api/voice", port),
+            ..BackendProfile::default()
+        };
+        let health = check_backend_health(&reqwest::Client::new(), &profile, None).await;
+
+        assert!(!health.reachable);
+        assert!(health.error.unwrap().starts_with("Backend not reachable"));
+    }
+}
@@ -0,0 +1,133 @@
+use super::retry::RetryProgress;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// How many cancelled job ids are remembered to suppress late insertions
+const CANCELLED_HISTORY: usize = 32;
+
+static JOB_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Sent to the UI as `voice-processing-progress`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgress<'a> {
+    pub job_id: &'a str,
+    #[serde(flatten)]
+    pub progress: &'a RetryProgress,
+}
+
+/// Sent to the UI as `voice-processing-started` and `voice-processing-cancelled`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobEvent {
+    pub job_id: String,
+}
+
+/// Voice processing jobs that are running or were recently cancelled
+#[derive(Debug, Default)]
+pub struct ProcessingJobs {
+    active: HashMap<String, CancellationToken>,
+    cancelled: VecDeque<String>,
+}
+
+impl ProcessingJobs {
+    /// Register a job, generating an id when the caller didn't pick one
+    pub fn start(&mut self, job_id: Option<String>) -> (String, CancellationToken) {
+        let job_id = job_id
+            .unwrap_or_else(|| format!("job-{}", JOB_COUNTER.fetch_add(1, Ordering::Relaxed)));
+        // A reused id starts over, so an earlier cancellation doesn't suppress this job
+        self.cancelled.retain(|id| *id != job_id);
+        let token = CancellationToken::new();
+        self.active.insert(job_id.clone(), token.clone());
+        debug!("Started voice processing job {}", job_id);
+        (job_id, token)
+    }
+
+    pub fn finish(&mut self, job_id: &str) {
+        self.active.remove(job_id);
+    }
+
+    /// Cancel a running job; false if no such job is running
+    pub fn cancel(&mut self, job_id: &str) -> bool {
+        let Some(token) = self.active.remove(job_id) else {
+            return false;
+        };
+        token.cancel();
+        if self.cancelled.len() == CANCELLED_HISTORY {
+            self.cancelled.pop_front();
+        }
+        self.cancelled.push_back(job_id.to_string());
+        info!("Cancelled voice processing job {}", job_id);
+        true
+    }
+
+    /// Cancel every running job, returning their ids
+    pub fn cancel_all(&mut self) -> Vec<String> {
+        let ids: Vec<String> = self.active.keys().cloned().collect();
+        ids.into_iter().filter(|id| self.cancel(id)).collect()
+    }
+
+    /// Whether the job was cancelled, so its result must not be inserted
+    pub fn is_cancelled(&self, job_id: &str) -> bool {
+        self.cancelled.iter().any(|id| id == job_id)
+    }
+}
+
+/// Tell the UI that jobs were cancelled
+pub fn emit_cancelled(app: &AppHandle, job_ids: &[String]) {
+    for job_id in job_ids {
+        let event = JobEvent {
+            job_id: job_id.clone(),
+        };
+        if let Err(e) = app.emit("voice-processing-cancelled", &event) {
+            warn!("Failed to emit cancellation of {}: {}", job_id, e);
+        }
+    }
+}
+
+/// Abort a running voice processing job and anything it would have typed
+#[tauri::command]
+pub async fn cancel_voice_processing(
+    app: AppHandle,
+    job_id: String,
+    jobs: State<'_, Mutex<ProcessingJobs>>,
+) -> Result<bool, String> {
+    let cancelled = jobs.lock().map_err(|e| e.to_string())?.cancel(&job_id);
+    if cancelled {
+        emit_cancelled(&app, &[job_id]);
+    }
+    Ok(cancelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelled_jobs_are_remembered() {
+        let mut jobs = ProcessingJobs::default();
+        let (job_id, token) = jobs.start(None);
+        assert!(jobs.cancel(&job_id));
+        assert!(token.is_cancelled());
+        assert!(jobs.is_cancelled(&job_id));
+        assert!(!jobs.cancel(&job_id));
+    }
+
+    #[test]
+    fn reused_id_is_not_cancelled() {
+        let mut jobs = ProcessingJobs::default();
+        let (job_id, _) = jobs.start(Some("dictation".to_string()));
+        jobs.cancel(&job_id);
+
+        let (job_id, token) = jobs.start(Some("dictation".to_string()));
+        assert!(!jobs.is_cancelled(&job_id));
+        assert!(!token.is_cancelled());
+        jobs.finish(&job_id);
+        assert!(!jobs.is_cancelled(&job_id));
+    }
+}
@@ -0,0 +1,176 @@
+//! In-process fake of the voice processing API for tests
+
+use super::config::BackendProfile;
+use super::retry::RetryPolicy;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tiny_http::{Header, Response, Server};
+
+/// Scripted answer to one request, served in order
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// 200 with `success: true` and the given final text
+    Success(&'static str),
+    /// 200 with `success: false` and the given error
+    Failure(&'static str),
+    /// Bare status code with a plain-text body
+    Status(u16),
+    /// 200 with a body that isn't valid JSON
+    Malformed,
+    /// Wait before answering with a success
+    Slow(Duration),
+    /// 401 for an expired or invalid access token
+    Unauthorized,
+    /// 200 with an arbitrary JSON body
+    Json(&'static str),
+}
+
+/// What the mock received
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub content_type: Option<String>,
+    pub authorization: Option<String>,
+    /// Every header as sent, in order
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Value of the first header with this name, compared case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Value of a text field in a multipart body
+    pub fn form_field(&self, name: &str) -> Option<String> {
+        let body = String::from_utf8_lossy(&self.body);
+        let marker = format!("name=\"{}\"\r\n\r\n", name);
+        let start = body.find(&marker)? + marker.len();
+        let end = body[start..].find("\r\n")?;
+        Some(body[start..start + end].to_string())
+    }
+}
+
+/// Local HTTP server that answers with scripted responses and records every request.
+/// Requests beyond the script get a 500.
+pub struct MockBackend {
+    server: Arc<Server>,
+    base_url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockBackend {
+    pub fn start(script: impl IntoIterator<Item = MockResponse>) -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("mock backend should bind"));
+        let address = server.server_addr().to_ip().expect("mock backend uses TCP");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let mut script: VecDeque<MockResponse> = script.into_iter().collect();
+        let (listener, recorded) = (server.clone(), requests.clone());
+        std::thread::spawn(move || {
+            for mut request in listener.incoming_requests() {
+                let mut recorded_request = RecordedRequest {
+                    method: request.method().to_string(),
+                    path: request.url().to_string(),
+                    content_type: header(&request, "Content-Type"),
+                    authorization: header(&request, "Authorization"),
+                    headers: request
+                        .headers()
+                        .iter()
+                        .map(|header| (header.field.to_string(), header.value.to_string()))
+                        .collect(),
+                    body: Vec::new(),
+                };
+                let _ = request.as_reader().read_to_end(&mut recorded_request.body);
+                recorded.lock().unwrap().push(recorded_request);
+
+                // The client may have given up already, so failed writes are fine
+                let _ = request.respond(respond(script.pop_front()));
+            }
+        });
+
+        Self {
+            server,
+            base_url: format!("http://{}/api/voice", address),
+            requests,
+        }
+    }
+
+    /// Profile pointing at this mock, with a short timeout and fast retries
+    pub fn profile(&self) -> BackendProfile {
+        BackendProfile {
+            name: "mock".to_string(),
+            base_url: self.base_url.clone(),
+            timeout_secs: 1,
+            retry: RetryPolicy {
+                max_attempts: 3,
+                initial_backoff_ms: 10,
+                max_backoff_ms: 20,
+                max_total_secs: 10,
+            },
+            ..BackendProfile::default()
+        }
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockBackend {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+fn header(request: &tiny_http::Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.to_string())
+}
+
+fn json(status: u16, body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(content_type)
+}
+
+fn respond(scripted: Option<MockResponse>) -> Response<std::io::Cursor<Vec<u8>>> {
+    match scripted {
+        Some(MockResponse::Success(text)) => json(
+            200,
+            serde_json::json!({
+                "success": true,
+                "transcription": {"text": text, "confidence": 0.9, "language": "en"},
+                "finalText": text,
+            })
+            .to_string(),
+        ),
+        Some(MockResponse::Failure(error)) => json(
+            200,
+            serde_json::json!({"success": false, "error": error}).to_string(),
+        ),
+        Some(MockResponse::Status(status)) => {
+            Response::from_string(format!("Mock status {}", status)).with_status_code(status)
+        }
+        Some(MockResponse::Malformed) => json(200, r#"{"success": tru"#.to_string()),
+        Some(MockResponse::Slow(delay)) => {
+            std::thread::sleep(delay);
+            respond(Some(MockResponse::Success("slow")))
+        }
+        Some(MockResponse::Unauthorized) => {
+            Response::from_string("Invalid token").with_status_code(401)
+        }
+        Some(MockResponse::Json(body)) => json(200, body.to_string()),
+        None => Response::from_string("Unexpected request").with_status_code(500),
+    }
+}
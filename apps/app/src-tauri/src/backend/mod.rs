@@ -0,0 +1,784 @@
+pub mod config;
+pub mod health;
+pub mod http;
+pub mod jobs;
+#[cfg(test)]
+pub mod mock;
+pub mod providers;
+pub mod queue;
+pub mod retry;
+pub mod tokens;
+pub mod upload;
+
+use crate::app_profiles::{self, AppProfile};
+use crate::auth::StoredTokenSource;
+use crate::context::{VoiceContext, gather_voice_context};
+use crate::context_policy::{self, ContextPolicy};
+use crate::dictionary::app_dictionary;
+use crate::voice_commands;
+use crate::pipeline;
+use base64::{Engine as _, engine::general_purpose};
+use config::{BackendConfigStore, BackendProfile, BackendSettings};
+use health::{check_backend_health, BackendHealth};
+use http::{app_client, shared_client, HttpClient};
+use jobs::{JobEvent, JobProgress, ProcessingJobs};
+use providers::{ProviderConfig, Transcript, TranscriptionAudio};
+use queue::OfflineQueue;
+use reqwest;
+use retry::{AttemptError, RetryProgress};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tokens::TokenSource;
+use tauri::{AppHandle, Emitter, Manager, State};
+use upload::{AudioSource, UploadFormat};
+use tracing::{debug, error, info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceProcessRequest {
+    pub audio: String,  // Base64 encoded audio
+    pub format: String,
+    pub context: VoiceContext,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceProcessResponse {
+    pub success: bool,
+    pub transcription: Option<TranscriptionResult>,
+    pub final_text: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionResult {
+    pub text: String,
+    pub confidence: Option<f32>,
+    pub language: Option<String>,
+    /// Timed segments, empty when the server doesn't provide them
+    #[serde(default, deserialize_with = "lenient_list")]
+    pub segments: Vec<TranscriptionSegment>,
+    /// Word-level timestamps, empty when the server doesn't provide them
+    #[serde(default, deserialize_with = "lenient_list")]
+    pub words: Vec<WordTiming>,
+    /// Other likely transcriptions, best first
+    #[serde(default, deserialize_with = "lenient_list")]
+    pub alternatives: Vec<TranscriptionAlternative>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionSegment {
+    /// Offsets into the recording, in seconds
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    /// Average token log probability, as reported by Whisper-style models
+    #[serde(default, alias = "avg_logprob")]
+    pub avg_logprob: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordTiming {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+    #[serde(default, alias = "probability")]
+    pub confidence: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionAlternative {
+    pub text: String,
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+/// Deserialize a list, treating null or a non-list as empty and skipping malformed
+/// entries, so a server with a slightly different shape can't fail the whole response
+fn lenient_list<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let items = match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Null => return Ok(Vec::new()),
+        other => {
+            warn!("Ignoring unexpected transcription detail: {}", other);
+            return Ok(Vec::new());
+        }
+    };
+
+    Ok(items
+        .into_iter()
+        .filter_map(|item| match serde_json::from_value(item) {
+            Ok(item) => Some(item),
+            Err(e) => {
+                warn!("Skipping malformed transcription detail: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Process audio with the custom backend API, retrying transient failures.
+/// `on_progress` is called before each attempt and before waiting to retry.
+/// A transient error means the backend could not be reached at all.
+/// With `tokens` set, requests carry the user's access token and a 401 triggers one refresh.
+pub async fn process_voice_recording(
+    client: &reqwest::Client,
+    audio: AudioSource,
+    context: Option<VoiceContext>,
+    profile: &BackendProfile,
+    tokens: Option<&dyn TokenSource>,
+    on_progress: impl FnMut(&RetryProgress),
+) -> Result<VoiceProcessResponse, AttemptError> {
+    info!("Processing voice recording with backend profile '{}'", profile.name);
+    match &audio {
+        AudioSource::Memory(bytes) => info!("Audio data size: {} bytes", bytes.len()),
+        AudioSource::File(path) => info!("Audio file: {:?}", path),
+    }
+
+    // Gather context if not provided
+    let voice_context = context.unwrap_or_else(|| {
+        debug!("No context provided, gathering current context");
+        let mut context = gather_voice_context();
+        ContextPolicy::default().apply(&mut context);
+        context
+    });
+
+    debug!("Using context: package={}, field_type={:?}", 
+           voice_context.package_name, voice_context.field_type);
+
+    // Send request to backend
+    let url = profile.url("");
+    let voice_response = retry::with_retry(
+        &profile.retry,
+        &profile.base_url,
+        |attempt| send_voice_request(client, profile, &url, &audio, &voice_context, tokens, attempt),
+        on_progress,
+    )
+    .await?;
+
+    if !voice_response.success {
+        let error_msg = voice_response.error.unwrap_or_else(|| "Unknown backend error".to_string());
+        error!("Backend processing failed: {}", error_msg);
+        return Err(AttemptError::fatal(error_msg));
+    }
+
+    info!("Voice processing completed successfully");
+    if let Some(ref final_text) = voice_response.final_text {
+        debug!("Final text: {}", final_text);
+    }
+
+    Ok(voice_response)
+}
+
+/// Make a single request to the backend, classifying failures for retry.
+/// If the access token is rejected it is refreshed and the request sent once more.
+async fn send_voice_request(
+    client: &reqwest::Client,
+    profile: &BackendProfile,
+    url: &str,
+    audio: &AudioSource,
+    context: &VoiceContext,
+    tokens: Option<&dyn TokenSource>,
+    attempt: u32,
+) -> Result<VoiceProcessResponse, AttemptError> {
+    debug!("Sending request to backend: {} (attempt {})", url, attempt);
+
+    let token = tokens.and_then(|tokens| tokens.access_token());
+    let result = send_upload(client, profile, url, audio, context, token.as_deref()).await;
+
+    match (result, tokens) {
+        (Err(error), Some(tokens)) if error.status == Some(reqwest::StatusCode::UNAUTHORIZED) => {
+            info!("Backend rejected the access token, refreshing");
+            match tokens.refresh(token.as_deref()).await {
+                Some(fresh) => send_upload(client, profile, url, audio, context, Some(&fresh)).await,
+                None => Err(error),
+            }
+        }
+        (result, _) => result,
+    }
+}
+
+/// Upload as multipart unless the profile or an earlier rejection says otherwise
+async fn send_upload(
+    client: &reqwest::Client,
+    profile: &BackendProfile,
+    url: &str,
+    audio: &AudioSource,
+    context: &VoiceContext,
+    token: Option<&str>,
+) -> Result<VoiceProcessResponse, AttemptError> {
+    if !profile.upload_format.prefers_multipart(&profile.base_url) {
+        return send_json_request(client, profile, url, audio, context, token).await;
+    }
+
+    let form = upload::multipart_form(audio, context)
+        .await
+        .map_err(AttemptError::fatal)?;
+    let response = authorize(profile.apply(client.post(url)), token)
+        .multipart(form)
+        .send()
+        .await
+        .map_err(AttemptError::from_send_error)?;
+
+    let status = response.status();
+    if profile.upload_format != UploadFormat::Auto || !upload::is_multipart_rejection(status) {
+        return read_voice_response(response).await;
+    }
+
+    // Older servers only understand the JSON body
+    warn!("Backend rejected multipart upload with {}, retrying as JSON", status);
+    let result = send_json_request(client, profile, url, audio, context, token).await;
+    if result.is_ok() {
+        upload::remember_legacy_backend(&profile.base_url);
+    }
+    result
+}
+
+/// Send the recording base64-encoded inside a JSON body
+async fn send_json_request(
+    client: &reqwest::Client,
+    profile: &BackendProfile,
+    url: &str,
+    audio: &AudioSource,
+    context: &VoiceContext,
+    token: Option<&str>,
+) -> Result<VoiceProcessResponse, AttemptError> {
+    // Encode audio as base64
+    let audio_data = audio.read().await.map_err(AttemptError::fatal)?;
+    let base64_audio = general_purpose::STANDARD.encode(&audio_data);
+    debug!("Audio encoded to base64: {} characters", base64_audio.len());
+
+    // Create request payload
+    let request_payload = VoiceProcessRequest {
+        audio: base64_audio,
+        format: "wav".to_string(),
+        context: context.clone(),
+    };
+
+    let response = authorize(profile.apply(client.post(url)), token)
+        .json(&request_payload)
+        .send()
+        .await
+        .map_err(AttemptError::from_send_error)?;
+
+    read_voice_response(response).await
+}
+
+/// Attach the user's access token, if any
+fn authorize(request: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Check the status and parse the backend's JSON answer
+async fn read_voice_response(response: reqwest::Response) -> Result<VoiceProcessResponse, AttemptError> {
+    let status = response.status();
+    debug!("Backend response status: {}", status);
+
+    if !status.is_success() {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let error_text = response.text().await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        error!("Backend error {}: {}", status, error_text);
+        return Err(AttemptError::from_status(status, retry_after.as_deref(), &error_text));
+    }
+
+    // Parse response
+    let response_text = response.text().await
+        .map_err(|e| AttemptError::invalid_response(format!("Failed to read response: {}", e)))?;
+    
+    debug!("Backend response: {}", response_text);
+
+    serde_json::from_str(&response_text)
+        .map_err(|e| AttemptError::invalid_response(format!("Failed to parse response: {}", e)))
+}
+
+/// Convert audio blob to the format expected by Groq (16kHz, mono, WAV)
+pub fn convert_audio_to_groq_format(audio_data: Vec<u8>) -> Result<Vec<u8>, String> {
+    info!("Audio format conversion - ensuring WAV 16kHz mono format");
+    debug!("Original audio size: {} bytes", audio_data.len());
+    
+    // The audio data coming from the recorder is already in the right format:
+    // - 16kHz sample rate (VOICE_SAMPLE_RATE in thread.rs)
+    // - Mono (preferred by get_optimal_config)
+    // - f32 samples converted to bytes
+    
+    // For now, we'll assume the audio is already in the correct format
+    // since our recorder is configured to output 16kHz mono audio
+    // In a production app, you might want to use a library like `hound` 
+    // to properly validate and convert the audio format
+    
+    // Validate that we have reasonable audio data
+    if audio_data.is_empty() {
+        return Err("Audio data is empty".to_string());
+    }
+    
+    if audio_data.len() < 1000 {
+        warn!("Audio data seems very short: {} bytes", audio_data.len());
+    }
+    
+    debug!("Audio format validated - {} bytes ready for Groq", audio_data.len());
+    Ok(audio_data)
+}
+
+/// Get the active backend profile from app state
+fn active_profile(store: &State<'_, Mutex<BackendConfigStore>>) -> Result<BackendProfile, String> {
+    let store = store.lock().map_err(|e| e.to_string())?;
+    Ok(store.active_profile())
+}
+
+/// The backend profile an app profile asks for, or the active one
+fn profile_for_app(
+    store: &State<'_, Mutex<BackendConfigStore>>,
+    app_profile: Option<&AppProfile>,
+) -> Result<BackendProfile, String> {
+    let store = store.lock().map_err(|e| e.to_string())?;
+    let Some(name) = app_profile.and_then(|p| p.backend_profile.as_deref()) else {
+        return Ok(store.active_profile());
+    };
+    Ok(store.profile(name).unwrap_or_else(|| {
+        warn!("Unknown backend profile '{}', using the active one", name);
+        store.active_profile()
+    }))
+}
+
+/// Save a JSON-only backend in its profile so later launches skip the multipart attempt
+pub(crate) fn save_detected_upload_format(app: &AppHandle, profile: &BackendProfile) {
+    if profile.upload_format != UploadFormat::Auto || !upload::is_legacy_backend(&profile.base_url) {
+        return;
+    }
+    let Some(store) = app.try_state::<Mutex<BackendConfigStore>>() else {
+        return;
+    };
+    let result = match store.lock() {
+        Ok(mut store) => store.remember_upload_format(&profile.name, UploadFormat::Json),
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = result {
+        warn!("Failed to save the upload format of '{}': {}", profile.name, e);
+    }
+}
+
+/// Tauri command to process voice recording with backend.
+/// If the backend can't be reached the recording is saved to the offline queue.
+/// The job can be aborted with `cancel_voice_processing` using `job_id`, or the id
+/// announced in the `voice-processing-started` event when none is given.
+#[tauri::command]
+pub async fn process_voice_with_backend(
+    app: AppHandle,
+    audio_data: Vec<u8>,
+    context: Option<VoiceContext>,
+    job_id: Option<String>,
+    store: State<'_, Mutex<BackendConfigStore>>,
+    queue: State<'_, Mutex<OfflineQueue>>,
+    jobs: State<'_, Mutex<ProcessingJobs>>,
+) -> Result<VoiceProcessResponse, String> {
+    debug!("Processing voice recording via Tauri command");
+    let client = app_client(&app);
+
+    // Capture context now so a queued recording keeps it
+    let mut context = context.unwrap_or_else(gather_voice_context);
+    let app_profile = app_profiles::resolve_for_app(&app, &context.package_name);
+    if let Some(app_profile) = &app_profile {
+        if !app_profile.dictation_allowed {
+            return Err(format!("Dictation is turned off for this app by profile '{}'", app_profile.name));
+        }
+        if let Some(language) = &app_profile.language {
+            context.locale = language.clone();
+        }
+    }
+    context.vocabulary = app_dictionary(&app).vocabulary();
+    // The cursor text stays unfiltered for fitting the result locally; only the copy
+    // that is uploaded or queued goes through the context policy
+    let mut shared = context.clone();
+    context_policy::apply_for_app(&app, &mut shared);
+    let profile = profile_for_app(&store, app_profile.as_ref())?;
+    let pipeline_name = app_profile.as_ref().and_then(|p| p.pipeline.as_deref());
+
+    // Convert audio to proper format
+    let converted_audio = convert_audio_to_groq_format(audio_data)
+        .map_err(|e| format!("Audio conversion failed: {}", e))?;
+
+    let (job_id, cancel_token) = jobs.lock().map_err(|e| e.to_string())?.start(job_id);
+    let _ = app.emit("voice-processing-started", JobEvent { job_id: job_id.clone() });
+    
+    // Process with backend; dropping the request future on cancellation aborts it
+    let audio = AudioSource::from(converted_audio);
+    let tokens = StoredTokenSource::new(app.clone());
+    let result = tokio::select! {
+        _ = cancel_token.cancelled() => None,
+        result = process_voice_recording(&client, audio.clone(), Some(shared.clone()), &profile, Some(&tokens), |progress| {
+            let progress = JobProgress { job_id: &job_id, progress };
+            if let Err(e) = app.emit("voice-processing-progress", progress) {
+                warn!("Failed to emit voice processing progress: {}", e);
+            }
+        }) => Some(result),
+    };
+
+    save_detected_upload_format(&app, &profile);
+
+    let cancelled = {
+        let mut jobs = jobs.lock().map_err(|e| e.to_string())?;
+        jobs.finish(&job_id);
+        jobs.is_cancelled(&job_id)
+    };
+    let result = match result {
+        Some(result) if !cancelled => result,
+        _ => {
+            info!("Voice processing job {} was cancelled", job_id);
+            return Err(format!("Voice processing job {} was cancelled", job_id));
+        }
+    };
+
+    match result {
+        Ok(mut response) => {
+            pipeline::apply_to_response(&app, &mut response, &context, pipeline_name).await;
+            Ok(response)
+        }
+        Err(error) if error.retryable => {
+            let audio_data = audio.read().await?;
+            let queue = queue.lock().map_err(|e| e.to_string())?;
+            let app_profile = app_profile.as_ref().map(|p| p.name.as_str());
+            let item = queue.enqueue(&audio_data, Some(shared), app_profile, &error.message)?;
+            let _ = app.emit("offline-queue-updated", &item);
+            Err(format!("{} - saved to the offline queue as {}", error.message, item.id))
+        }
+        Err(error) => Err(error.message),
+    }
+}
+
+/// Check reachability, version, capabilities and sign-in of the active backend
+#[tauri::command]
+pub async fn test_backend_connection(
+    app: AppHandle,
+    store: State<'_, Mutex<BackendConfigStore>>,
+    http: State<'_, Mutex<HttpClient>>,
+) -> Result<BackendHealth, String> {
+    let profile = active_profile(&store)?;
+    let tokens = StoredTokenSource::new(app);
+    let health = check_backend_health(&shared_client(&http)?, &profile, Some(&tokens)).await;
+    if let Some(error) = &health.error {
+        warn!("Backend health check for '{}': {}", profile.name, error);
+    }
+    Ok(health)
+}
+
+/// Transcribe WAV audio directly with a speech-to-text provider.
+/// Spoken commands are applied, and dictionary terms passed as a hint and corrected.
+/// `package_name` is the app the recording was made in, for its app profile.
+#[tauri::command]
+pub async fn transcribe_with_provider(
+    app: AppHandle,
+    audio_data: Vec<u8>,
+    mut provider: ProviderConfig,
+    package_name: Option<String>,
+    http: State<'_, Mutex<HttpClient>>,
+) -> Result<Transcript, String> {
+    let app_profile =
+        app_profiles::resolve_for_app(&app, package_name.as_deref().unwrap_or_default());
+    if let Some(app_profile) = &app_profile {
+        if !app_profile.dictation_allowed {
+            return Err(format!("Dictation is turned off for this app by profile '{}'", app_profile.name));
+        }
+        if let Some(profile_provider) = &app_profile.provider {
+            debug!("Using the provider from app profile '{}'", app_profile.name);
+            provider = profile_provider.clone();
+        }
+    }
+    let profile_language = app_profile.as_ref().and_then(|p| p.language.clone());
+    if let Some(language) = &profile_language {
+        provider.set_language(language);
+    }
+
+    let converted_audio = convert_audio_to_groq_format(audio_data)
+        .map_err(|e| format!("Audio conversion failed: {}", e))?;
+    let client = shared_client(&http)?;
+    let dictionary = app_dictionary(&app);
+    provider.add_vocabulary_hint(&dictionary.vocabulary());
+    let mut transcript =
+        providers::transcribe(&provider, &client, &TranscriptionAudio::wav(converted_audio)).await?;
+    let language = profile_language
+        .as_deref()
+        .or(transcript.language.as_deref())
+        .unwrap_or("en");
+    let commanded = voice_commands::apply_for_app(&app, &transcript.text, language);
+    transcript.text = dictionary.apply(&commanded);
+    Ok(transcript)
+}
+
+/// Get all backend endpoint profiles
+#[tauri::command]
+pub async fn get_backend_settings(
+    store: State<'_, Mutex<BackendConfigStore>>,
+) -> Result<BackendSettings, String> {
+    let store = store.lock().map_err(|e| e.to_string())?;
+    Ok(store.settings().clone())
+}
+
+/// Replace and save all backend endpoint profiles
+#[tauri::command]
+pub async fn set_backend_settings(
+    settings: BackendSettings,
+    store: State<'_, Mutex<BackendConfigStore>>,
+) -> Result<(), String> {
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    store.set_settings(settings)
+}
+
+/// Switch to another named backend profile
+#[tauri::command]
+pub async fn select_backend_profile(
+    name: String,
+    store: State<'_, Mutex<BackendConfigStore>>,
+) -> Result<(), String> {
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    store.select_profile(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    use mock::{MockBackend, MockResponse};
+    use std::time::{Duration, Instant};
+
+    /// Token source that hands out a stale token and refreshes it to a fresh one
+    struct RotatingTokens {
+        current: Mutex<String>,
+    }
+
+    #[async_trait::async_trait]
+    impl TokenSource for RotatingTokens {
+        fn access_token(&self) -> Option<String> {
+            Some(self.current.lock().unwrap().clone())
+        }
+
+        async fn refresh(&self, _rejected: Option<&str>) -> Option<String> {
+            let mut current = self.current.lock().unwrap();
+            *current = "fresh".to_string();
+            Some(current.clone())
+        }
+    }
+
+    async fn process(
+        backend: &MockBackend,
+        tokens: Option<&dyn TokenSource>,
+    ) -> Result<VoiceProcessResponse, AttemptError> {
+        process_voice_recording(
+            &reqwest::Client::new(),
+            AudioSource::from(b"RIFF mock audio".to_vec()),
+            Some(VoiceContext::default()),
+            &backend.profile(),
+            tokens,
+            |_| {},
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn uploads_recording_and_parses_response() {
+        let backend = MockBackend::start([MockResponse::Success("hello world")]);
+        let response = process(&backend, None).await.unwrap();
+
+        assert_eq!(response.final_text.as_deref(), Some("hello world"));
+        assert_eq!(response.transcription.unwrap().language.as_deref(), Some("en"));
+        let requests = backend.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/api/voice");
+        assert!(requests[0].content_type.as_deref().unwrap().starts_with("multipart/form-data"));
+        assert!(requests[0].authorization.is_none());
+        let body = String::from_utf8_lossy(&requests[0].body);
+        assert!(body.contains("RIFF mock audio"));
+        assert!(body.contains("name=\"context\""));
+    }
+
+    #[tokio::test]
+    async fn server_error_fails_without_retrying() {
+        let backend = MockBackend::start([MockResponse::Status(500)]);
+        let error = process(&backend, None).await.unwrap_err();
+
+        assert!(!error.retryable);
+        assert_eq!(error.status, Some(reqwest::StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(error.message.contains("Mock status 500"), "{}", error);
+        assert_eq!(backend.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unavailable_backend_is_retried() {
+        let backend = MockBackend::start([MockResponse::Status(503), MockResponse::Success("ok")]);
+        let mut attempts = Vec::new();
+        let response = process_voice_recording(
+            &reqwest::Client::new(),
+            AudioSource::from(b"RIFF".to_vec()),
+            Some(VoiceContext::default()),
+            &backend.profile(),
+            None,
+            |progress| attempts.push(progress.attempt),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.final_text.as_deref(), Some("ok"));
+        assert_eq!(backend.requests().len(), 2);
+        assert!(attempts.contains(&2));
+    }
+
+    #[tokio::test]
+    async fn malformed_json_is_reported() {
+        let backend = MockBackend::start([MockResponse::Malformed]);
+        let error = process(&backend, None).await.unwrap_err();
+
+        assert!(!error.retryable);
+        assert!(error.message.starts_with("Failed to parse response"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn unsuccessful_response_carries_backend_error() {
+        let backend = MockBackend::start([MockResponse::Failure("No speech detected")]);
+        let error = process(&backend, None).await.unwrap_err();
+
+        assert_eq!(error.message, "No speech detected");
+        assert!(!error.retryable);
+    }
+
+    #[tokio::test]
+    async fn slow_backend_times_out() {
+        let backend = MockBackend::start([MockResponse::Slow(Duration::from_secs(3))]);
+        let started = Instant::now();
+        let error = process(&backend, None).await.unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(3), "{:?}", started.elapsed());
+        assert!(!error.retryable);
+        assert_eq!(error.status, None);
+    }
+
+    #[tokio::test]
+    async fn rejected_token_is_refreshed_once() {
+        let backend = MockBackend::start([MockResponse::Unauthorized, MockResponse::Success("ok")]);
+        let tokens = RotatingTokens { current: Mutex::new("stale".to_string()) };
+        let response = process(&backend, Some(&tokens)).await.unwrap();
+
+        assert_eq!(response.final_text.as_deref(), Some("ok"));
+        let authorization: Vec<_> = backend
+            .requests()
+            .into_iter()
+            .map(|request| request.authorization.unwrap_or_default())
+            .collect();
+        assert_eq!(authorization, vec!["Bearer stale", "Bearer fresh"]);
+    }
+
+    #[tokio::test]
+    async fn unauthorized_without_tokens_fails() {
+        let backend = MockBackend::start([MockResponse::Unauthorized]);
+        let error = process(&backend, None).await.unwrap_err();
+
+        assert_eq!(error.status, Some(reqwest::StatusCode::UNAUTHORIZED));
+        assert_eq!(backend.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn legacy_backend_gets_json_uploads() {
+        let backend = MockBackend::start([
+            MockResponse::Status(415),
+            MockResponse::Success("first"),
+            MockResponse::Success("second"),
+        ]);
+        process(&backend, None).await.unwrap();
+        let response = process(&backend, None).await.unwrap();
+        assert_eq!(response.final_text.as_deref(), Some("second"));
+
+        let content_types: Vec<_> = backend
+            .requests()
+            .into_iter()
+            .map(|request| request.content_type.unwrap_or_default())
+            .collect();
+        assert!(content_types[0].starts_with("multipart/form-data"));
+        assert_eq!(content_types[1..], ["application/json", "application/json"]);
+        let legacy: VoiceProcessRequest =
+            serde_json::from_slice(&backend.requests()[2].body).unwrap();
+        assert_eq!(legacy.format, "wav");
+    }
+
+    #[tokio::test]
+    async fn bad_request_does_not_switch_to_json() {
+        let backend = MockBackend::start([MockResponse::Status(400), MockResponse::Status(422)]);
+        for status in [400, 422] {
+            let error = process(&backend, None).await.unwrap_err();
+            assert_eq!(error.status.map(|s| s.as_u16()), Some(status));
+        }
+
+        let requests = backend.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|request| request.content_type.as_deref().unwrap().starts_with("multipart/form-data")));
+        assert!(!upload::is_legacy_backend(&backend.profile().base_url));
+    }
+
+    #[test]
+    fn parses_transcription_from_older_servers() {
+        let result: TranscriptionResult =
+            serde_json::from_str(r#"{"text": "hello", "confidence": 0.9, "language": "en"}"#).unwrap();
+        assert_eq!(result.text, "hello");
+        assert!(result.segments.is_empty());
+        assert!(result.words.is_empty());
+        assert!(result.alternatives.is_empty());
+    }
+
+    #[test]
+    fn parses_segments_words_and_alternatives() {
+        let result: TranscriptionResult = serde_json::from_str(
+            r#"{
+                "text": "hello world",
+                "confidence": null,
+                "language": "en",
+                "segments": [{"id": 0, "start": 0.0, "end": 1.2, "text": "hello world", "avg_logprob": -0.25}],
+                "words": [
+                    {"word": "hello", "start": 0.0, "end": 0.5, "probability": 0.98},
+                    {"word": "world", "start": 0.6, "end": 1.2}
+                ],
+                "alternatives": [{"text": "hello word", "confidence": 0.4}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(result.segments[0].avg_logprob, Some(-0.25));
+        assert_eq!(result.words.len(), 2);
+        assert_eq!(result.words[0].confidence, Some(0.98));
+        assert_eq!(result.words[1].confidence, None);
+        assert_eq!(result.alternatives[0].text, "hello word");
+    }
+
+    #[test]
+    fn skips_malformed_transcription_details() {
+        let result: TranscriptionResult = serde_json::from_str(
+            r#"{
+                "text": "hi",
+                "confidence": null,
+                "language": null,
+                "segments": null,
+                "words": [{"word": "hi"}, {"word": "hi", "start": 0, "end": 0.3}],
+                "alternatives": "none"
+            }"#,
+        )
+        .unwrap();
+        assert!(result.segments.is_empty());
+        assert_eq!(result.words.len(), 1);
+        assert!(result.alternatives.is_empty());
+    }
+}
+
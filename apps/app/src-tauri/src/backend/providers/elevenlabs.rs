@@ -0,0 +1,179 @@
+use super::{error_from_response, Transcript, TranscriptionAudio, TranscriptionProvider};
+use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+const ELEVENLABS_STT_URL: &str = "https://api.elevenlabs.io/v1/speech-to-text";
+
+/// Options for the ElevenLabs speech-to-text API - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElevenLabsConfig {
+    pub api_key: String,
+    /// e.g. `scribe_v1`
+    #[serde(default = "default_model_id")]
+    pub model_id: String,
+    /// ISO-639-1 or ISO-639-3 code; detected automatically when absent
+    pub language_code: Option<String>,
+    /// Whether to annotate sounds like (laughter) in the transcript
+    #[serde(default)]
+    pub tag_audio_events: bool,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_model_id() -> String {
+    "scribe_v1".to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Deserialize)]
+struct SpeechToTextResponse {
+    text: String,
+    language_code: Option<String>,
+}
+
+pub struct ElevenLabsProvider {
+    config: ElevenLabsConfig,
+    client: reqwest::Client,
+    url: String,
+}
+
+impl ElevenLabsProvider {
+    pub fn new(config: ElevenLabsConfig, client: reqwest::Client) -> Self {
+        Self {
+            config,
+            client,
+            url: ELEVENLABS_STT_URL.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for ElevenLabsProvider {
+    fn name(&self) -> &str {
+        "elevenlabs"
+    }
+
+    async fn transcribe(&self, audio: &TranscriptionAudio) -> Result<Transcript, String> {
+        let file = Part::bytes(audio.bytes.clone())
+            .file_name(audio.file_name.clone())
+            .mime_str(&audio.mime_type)
+            .map_err(|e| format!("Invalid audio MIME type: {}", e))?;
+
+        let mut form = Form::new()
+            .part("file", file)
+            .text("model_id", self.config.model_id.clone())
+            .text("tag_audio_events", self.config.tag_audio_events.to_string());
+        if let Some(language_code) = &self.config.language_code {
+            form = form.text("language_code", language_code.clone());
+        }
+
+        debug!("Sending transcription request to {}", self.url);
+        let response = self
+            .client
+            .post(&self.url)
+            .header("xi-api-key", &self.config.api_key)
+            .multipart(form)
+            .timeout(std::time::Duration::from_secs(self.config.timeout_secs))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(self.name(), response).await);
+        }
+
+        let body: SpeechToTextResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        Ok(Transcript {
+            text: body.text.trim().to_string(),
+            language: body.language_code,
+            duration_seconds: None,
+            provider: self.name().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{MockBackend, MockResponse};
+
+    fn provider(mock: &MockBackend, language_code: Option<&str>) -> ElevenLabsProvider {
+        ElevenLabsProvider {
+            config: ElevenLabsConfig {
+                api_key: "xi-key".to_string(),
+                model_id: default_model_id(),
+                language_code: language_code.map(str::to_string),
+                tag_audio_events: true,
+                timeout_secs: 5,
+            },
+            client: reqwest::Client::new(),
+            url: format!("{}/speech-to-text", mock.profile().base_url),
+        }
+    }
+
+    fn audio() -> TranscriptionAudio {
+        TranscriptionAudio::wav(b"RIFF fake wav".to_vec())
+    }
+
+    #[tokio::test]
+    async fn sends_key_and_form_fields() {
+        let mock = MockBackend::start([MockResponse::Json(
+            r#"{"text": " Hallo Welt ", "language_code": "deu"}"#,
+        )]);
+        let transcript = provider(&mock, Some("de"))
+            .transcribe(&audio())
+            .await
+            .unwrap();
+
+        assert_eq!(transcript.text, "Hallo Welt");
+        assert_eq!(transcript.language.as_deref(), Some("deu"));
+        assert_eq!(transcript.provider, "elevenlabs");
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/voice/speech-to-text");
+        assert_eq!(request.header("xi-api-key"), Some("xi-key"));
+        assert!(request
+            .content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.starts_with("multipart/form-data")));
+        assert_eq!(request.form_field("model_id").as_deref(), Some("scribe_v1"));
+        assert_eq!(
+            request.form_field("tag_audio_events").as_deref(),
+            Some("true")
+        );
+        assert_eq!(request.form_field("language_code").as_deref(), Some("de"));
+        assert!(String::from_utf8_lossy(&request.body).contains("RIFF fake wav"));
+    }
+
+    #[tokio::test]
+    async fn leaves_out_unset_language() {
+        let mock = MockBackend::start([MockResponse::Json(r#"{"text": "hi"}"#)]);
+        let transcript = provider(&mock, None).transcribe(&audio()).await.unwrap();
+
+        assert_eq!(transcript.language, None);
+        assert_eq!(mock.requests()[0].form_field("language_code"), None);
+    }
+
+    #[tokio::test]
+    async fn reports_error_status_and_bad_json() {
+        let mock = MockBackend::start([MockResponse::Status(401), MockResponse::Malformed]);
+        let provider = provider(&mock, None);
+
+        let err = provider.transcribe(&audio()).await.unwrap_err();
+        assert!(err.starts_with("elevenlabs error 401"), "{}", err);
+        let err = provider.transcribe(&audio()).await.unwrap_err();
+        assert!(err.starts_with("Failed to parse response"), "{}", err);
+    }
+}
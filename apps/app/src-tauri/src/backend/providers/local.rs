@@ -0,0 +1,264 @@
+use super::{Transcript, TranscriptionAudio, TranscriptionProvider};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::Duration;
+use tempfile::TempPath;
+use tokio::process::Command;
+use tracing::debug;
+
+/// Replaced with the path of the temporary WAV file
+const FILE_PLACEHOLDER: &str = "{file}";
+/// Replaced with the configured language, or `auto` when none is set
+const LANGUAGE_PLACEHOLDER: &str = "{language}";
+
+/// How to read the command's standard output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LocalOutputFormat {
+    /// The whole output is the transcript
+    #[default]
+    Text,
+    /// The output is a JSON object; `text_field` is a dot-separated path to the transcript
+    Json {
+        #[serde(default = "default_text_field")]
+        text_field: String,
+    },
+}
+
+fn default_text_field() -> String {
+    "text".to_string()
+}
+
+/// Options for a local command-line engine such as whisper.cpp - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalCommandConfig {
+    /// Executable to run, e.g. `/usr/local/bin/whisper-cli`
+    pub program: String,
+    /// Arguments, with `{file}` and `{language}` placeholders,
+    /// e.g. `["-m", "ggml-base.bin", "-f", "{file}", "-l", "{language}", "-nt"]`
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub language: Option<String>,
+    #[serde(default)]
+    pub output_format: LocalOutputFormat,
+    pub working_dir: Option<String>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    120
+}
+
+/// Temporary audio file that is removed when dropped.
+///
+/// The file gets a random name and is created exclusively, readable only by the
+/// current user, so other local users can neither read the audio nor plant a
+/// symlink in its place.
+struct TempAudioFile(TempPath);
+
+impl TempAudioFile {
+    fn write(audio: &TranscriptionAudio) -> Result<Self, String> {
+        let extension = std::path::Path::new(&audio.file_name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("wav");
+        let file = tempfile::Builder::new()
+            .prefix("whisperme-")
+            .suffix(&format!(".{}", extension))
+            .tempfile()
+            .and_then(|mut file| {
+                file.write_all(&audio.bytes)?;
+                file.flush()?;
+                Ok(file)
+            })
+            .map_err(|e| format!("Failed to write temporary audio file: {}", e))?;
+        // Close our handle so the engine can open the file on every platform
+        Ok(Self(file.into_temp_path()))
+    }
+}
+
+pub struct LocalCommandProvider {
+    config: LocalCommandConfig,
+}
+
+impl LocalCommandProvider {
+    pub fn new(config: LocalCommandConfig) -> Self {
+        Self { config }
+    }
+
+    fn substitute(&self, arg: &str, file: &str) -> String {
+        arg.replace(FILE_PLACEHOLDER, file).replace(
+            LANGUAGE_PLACEHOLDER,
+            self.config.language.as_deref().unwrap_or("auto"),
+        )
+    }
+
+    fn parse_output(&self, stdout: &str) -> Result<(String, Option<String>), String> {
+        match &self.config.output_format {
+            LocalOutputFormat::Text => Ok((stdout.trim().to_string(), None)),
+            LocalOutputFormat::Json { text_field } => {
+                let value: serde_json::Value = serde_json::from_str(stdout.trim())
+                    .map_err(|e| format!("Failed to parse command output as JSON: {}", e))?;
+                let text = text_field
+                    .split('.')
+                    .try_fold(&value, |value, key| value.get(key))
+                    .and_then(|text| text.as_str())
+                    .ok_or_else(|| {
+                        format!("Command output has no string field '{}'", text_field)
+                    })?;
+                let language = value
+                    .get("language")
+                    .and_then(|language| language.as_str())
+                    .map(str::to_string);
+                Ok((text.trim().to_string(), language))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for LocalCommandProvider {
+    fn name(&self) -> &str {
+        "local-command"
+    }
+
+    async fn transcribe(&self, audio: &TranscriptionAudio) -> Result<Transcript, String> {
+        let temp_file = TempAudioFile::write(audio)?;
+        let file = temp_file.0.to_string_lossy().to_string();
+        let args: Vec<String> = self
+            .config
+            .args
+            .iter()
+            .map(|arg| self.substitute(arg, &file))
+            .collect();
+
+        debug!(
+            "Running local transcription: {} {:?}",
+            self.config.program, args
+        );
+        let mut command = Command::new(self.substitute(&self.config.program, &file));
+        command.args(&args).kill_on_drop(true);
+        if let Some(working_dir) = &self.config.working_dir {
+            command.current_dir(working_dir);
+        }
+
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let output = tokio::time::timeout(timeout, command.output())
+            .await
+            .map_err(|_| {
+                format!(
+                    "Local transcription timed out after {}s",
+                    self.config.timeout_secs
+                )
+            })?
+            .map_err(|e| format!("Failed to run '{}': {}", self.config.program, e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Local transcription exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let (text, language) = self.parse_output(&String::from_utf8_lossy(&output.stdout))?;
+        Ok(Transcript {
+            text,
+            language: language.or_else(|| self.config.language.clone()),
+            duration_seconds: None,
+            provider: self.name().to_string(),
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn stub(
+        script: &str,
+        output_format: LocalOutputFormat,
+        timeout_secs: u64,
+    ) -> LocalCommandProvider {
+        LocalCommandProvider::new(LocalCommandConfig {
+            program: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                script.to_string(),
+                "stub".to_string(),
+                "{file}".to_string(),
+                "{language}".to_string(),
+            ],
+            language: Some("de".to_string()),
+            output_format,
+            working_dir: None,
+            timeout_secs,
+        })
+    }
+
+    fn audio() -> TranscriptionAudio {
+        TranscriptionAudio::wav(b"RIFF fake wav".to_vec())
+    }
+
+    #[tokio::test]
+    async fn reads_plain_text_and_substitutes_placeholders() {
+        let provider = stub(
+            r#"test -f "$1" && echo "  hello $2  ""#,
+            LocalOutputFormat::Text,
+            10,
+        );
+        let transcript = provider.transcribe(&audio()).await.unwrap();
+        assert_eq!(transcript.text, "hello de");
+        assert_eq!(transcript.language.as_deref(), Some("de"));
+    }
+
+    #[tokio::test]
+    async fn reads_nested_json_field() {
+        let provider = stub(
+            r#"echo '{"result": {"text": " hallo "}, "language": "de"}'"#,
+            LocalOutputFormat::Json {
+                text_field: "result.text".to_string(),
+            },
+            10,
+        );
+        let transcript = provider.transcribe(&audio()).await.unwrap();
+        assert_eq!(transcript.text, "hallo");
+    }
+
+    #[test]
+    fn temp_file_is_private_and_removed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_file = TempAudioFile::write(&audio()).unwrap();
+        let path = temp_file.0.to_path_buf();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        assert!(
+            name.starts_with("whisperme-") && name.ends_with(".wav"),
+            "{}",
+            name
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), b"RIFF fake wav");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        drop(temp_file);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn fails_on_non_zero_exit() {
+        let provider = stub("echo boom >&2; exit 3", LocalOutputFormat::Text, 10);
+        let err = provider.transcribe(&audio()).await.unwrap_err();
+        assert!(err.contains("boom"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn enforces_timeout() {
+        let provider = stub("sleep 5", LocalOutputFormat::Text, 1);
+        let err = provider.transcribe(&audio()).await.unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+    }
+}
@@ -0,0 +1,150 @@
+pub mod elevenlabs;
+pub mod local;
+pub mod openai;
+
+use crate::recorder::{wav::encode_wav, AudioRecording};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+pub use elevenlabs::{ElevenLabsConfig, ElevenLabsProvider};
+pub use local::{LocalCommandConfig, LocalCommandProvider, LocalOutputFormat};
+pub use openai::{OpenAiCompatibleConfig, OpenAiCompatibleProvider};
+
+/// Audio handed to a transcription provider
+#[derive(Debug, Clone)]
+pub struct TranscriptionAudio {
+    /// Encoded audio file contents
+    pub bytes: Vec<u8>,
+    /// File name sent with the upload; providers use the extension to detect the format
+    pub file_name: String,
+    pub mime_type: String,
+}
+
+impl TranscriptionAudio {
+    /// Wrap WAV bytes as produced by the recorder
+    pub fn wav(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            file_name: "recording.wav".to_string(),
+            mime_type: "audio/wav".to_string(),
+        }
+    }
+}
+
+/// Transcription result shared by all providers - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcript {
+    pub text: String,
+    /// Detected or requested language, if the provider reports one
+    pub language: Option<String>,
+    pub duration_seconds: Option<f32>,
+    /// Name of the provider that produced the transcript
+    pub provider: String,
+}
+
+/// A speech-to-text service that turns audio into text
+#[async_trait]
+pub trait TranscriptionProvider: Send + Sync {
+    /// Short name used in logs and results
+    fn name(&self) -> &str;
+
+    async fn transcribe(&self, audio: &TranscriptionAudio) -> Result<Transcript, String>;
+}
+
+/// Provider selection with its options - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ProviderConfig {
+    /// Any `/audio/transcriptions` API: OpenAI, Groq or speaches
+    OpenAiCompatible(OpenAiCompatibleConfig),
+    ElevenLabs(ElevenLabsConfig),
+    /// An offline command-line engine run on a temporary WAV file
+    LocalCommand(LocalCommandConfig),
+}
+
+impl ProviderConfig {
+    /// Create the provider described by this configuration, sending requests with `client`
+    pub fn build(&self, client: reqwest::Client) -> Box<dyn TranscriptionProvider> {
+        match self {
+            ProviderConfig::OpenAiCompatible(config) => {
+                Box::new(OpenAiCompatibleProvider::new(config.clone(), client))
+            }
+            ProviderConfig::ElevenLabs(config) => {
+                Box::new(ElevenLabsProvider::new(config.clone(), client))
+            }
+            ProviderConfig::LocalCommand(config) => {
+                Box::new(LocalCommandProvider::new(config.clone()))
+            }
+        }
+    }
+
+    /// Bias recognition towards the given terms where the provider supports a prompt
+    pub fn add_vocabulary_hint(&mut self, terms: &[String]) {
+        if terms.is_empty() {
+            return;
+        }
+        match self {
+            ProviderConfig::OpenAiCompatible(config) => {
+                let hint = format!("Vocabulary: {}.", terms.join(", "));
+                config.prompt = Some(match config.prompt.take() {
+                    Some(prompt) if !prompt.trim().is_empty() => format!("{} {}", hint, prompt),
+                    _ => hint,
+                });
+            }
+            ProviderConfig::ElevenLabs(_) | ProviderConfig::LocalCommand(_) => {
+                debug!("Provider takes no vocabulary hint, relying on dictionary corrections");
+            }
+        }
+    }
+
+    /// Ask for a fixed language; a locale like `de-DE` is reduced to `de`
+    pub fn set_language(&mut self, locale: &str) {
+        let language = locale
+            .split(['-', '_'])
+            .next()
+            .unwrap_or(locale)
+            .to_lowercase();
+        match self {
+            ProviderConfig::OpenAiCompatible(config) => config.language = Some(language),
+            ProviderConfig::ElevenLabs(config) => config.language_code = Some(language),
+            ProviderConfig::LocalCommand(config) => config.language = Some(language),
+        }
+    }
+}
+
+/// Transcribe audio with the configured provider
+pub async fn transcribe(
+    config: &ProviderConfig,
+    client: &reqwest::Client,
+    audio: &TranscriptionAudio,
+) -> Result<Transcript, String> {
+    let provider = config.build(client.clone());
+    info!(
+        "Transcribing {} bytes with provider '{}'",
+        audio.bytes.len(),
+        provider.name()
+    );
+    provider.transcribe(audio).await
+}
+
+/// Encode a native recording and transcribe it, without going through the webview
+pub async fn transcribe_recording(
+    config: &ProviderConfig,
+    client: &reqwest::Client,
+    recording: &AudioRecording,
+) -> Result<Transcript, String> {
+    let wav = encode_wav(recording).map_err(|e| format!("Failed to encode recording: {}", e))?;
+    transcribe(config, client, &TranscriptionAudio::wav(wav)).await
+}
+
+/// Read an error body from a failed provider response
+async fn error_from_response(provider: &str, response: reqwest::Response) -> String {
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    format!("{} error {}: {}", provider, status, body)
+}
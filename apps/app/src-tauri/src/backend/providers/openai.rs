@@ -0,0 +1,221 @@
+use super::{error_from_response, Transcript, TranscriptionAudio, TranscriptionProvider};
+use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Options for an OpenAI-compatible `/audio/transcriptions` endpoint - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenAiCompatibleConfig {
+    /// e.g. `https://api.openai.com/v1`, `https://api.groq.com/openai/v1`,
+    /// or a local speaches server such as `http://localhost:8000/v1`
+    pub base_url: String,
+    /// Bearer token; optional for self-hosted servers
+    pub api_key: Option<String>,
+    /// e.g. `whisper-1`, `whisper-large-v3-turbo`, `Systran/faster-whisper-small`
+    pub model: String,
+    /// ISO-639-1 code; detected automatically when absent
+    pub language: Option<String>,
+    /// Text to guide spelling and style
+    pub prompt: Option<String>,
+    pub temperature: Option<f32>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    60
+}
+
+/// Fields we read from a `json` or `verbose_json` transcription response
+#[derive(Debug, Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+    language: Option<String>,
+    duration: Option<f32>,
+}
+
+pub struct OpenAiCompatibleProvider {
+    config: OpenAiCompatibleConfig,
+    client: reqwest::Client,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(config: OpenAiCompatibleConfig, client: reqwest::Client) -> Self {
+        Self { config, client }
+    }
+
+    fn build_form(&self, audio: &TranscriptionAudio) -> Result<Form, String> {
+        let file = Part::bytes(audio.bytes.clone())
+            .file_name(audio.file_name.clone())
+            .mime_str(&audio.mime_type)
+            .map_err(|e| format!("Invalid audio MIME type: {}", e))?;
+
+        let mut form = Form::new()
+            .part("file", file)
+            .text("model", self.config.model.clone())
+            .text("response_format", "verbose_json");
+
+        if let Some(language) = &self.config.language {
+            form = form.text("language", language.clone());
+        }
+        if let Some(prompt) = &self.config.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+        if let Some(temperature) = self.config.temperature {
+            form = form.text("temperature", temperature.to_string());
+        }
+        Ok(form)
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str {
+        "openai-compatible"
+    }
+
+    async fn transcribe(&self, audio: &TranscriptionAudio) -> Result<Transcript, String> {
+        let url = format!(
+            "{}/audio/transcriptions",
+            self.config.base_url.trim_end_matches('/')
+        );
+        debug!("Sending transcription request to {}", url);
+
+        let mut request = self
+            .client
+            .post(&url)
+            .multipart(self.build_form(audio)?)
+            .timeout(std::time::Duration::from_secs(self.config.timeout_secs));
+        if let Some(api_key) = &self.config.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(self.name(), response).await);
+        }
+
+        let body: TranscriptionResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        Ok(Transcript {
+            text: body.text.trim().to_string(),
+            language: body.language.or_else(|| self.config.language.clone()),
+            duration_seconds: body.duration,
+            provider: self.name().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{MockBackend, MockResponse};
+
+    fn config(mock: &MockBackend) -> OpenAiCompatibleConfig {
+        OpenAiCompatibleConfig {
+            // A trailing slash must not double up in the request path
+            base_url: format!("{}/v1/", mock.profile().base_url),
+            api_key: Some("sk-test".to_string()),
+            model: "whisper-1".to_string(),
+            language: Some("en".to_string()),
+            prompt: Some("Vocabulary: Kubernetes.".to_string()),
+            temperature: Some(0.2),
+            timeout_secs: 5,
+        }
+    }
+
+    fn audio() -> TranscriptionAudio {
+        TranscriptionAudio::wav(b"RIFF fake wav".to_vec())
+    }
+
+    #[tokio::test]
+    async fn sends_multipart_request() {
+        let mock = MockBackend::start([MockResponse::Json(
+            r#"{"text": " Hello there. ", "language": "english", "duration": 1.5}"#,
+        )]);
+        let provider = OpenAiCompatibleProvider::new(config(&mock), reqwest::Client::new());
+        let transcript = provider.transcribe(&audio()).await.unwrap();
+
+        assert_eq!(transcript.text, "Hello there.");
+        assert_eq!(transcript.language.as_deref(), Some("english"));
+        assert_eq!(transcript.duration_seconds, Some(1.5));
+        assert_eq!(transcript.provider, "openai-compatible");
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/voice/v1/audio/transcriptions");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer sk-test"));
+        assert!(request
+            .content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.starts_with("multipart/form-data")));
+        assert_eq!(request.form_field("model").as_deref(), Some("whisper-1"));
+        assert_eq!(
+            request.form_field("response_format").as_deref(),
+            Some("verbose_json")
+        );
+        assert_eq!(request.form_field("language").as_deref(), Some("en"));
+        assert_eq!(
+            request.form_field("prompt").as_deref(),
+            Some("Vocabulary: Kubernetes.")
+        );
+        assert_eq!(request.form_field("temperature").as_deref(), Some("0.2"));
+        let body = String::from_utf8_lossy(&request.body);
+        assert!(body.contains(r#"filename="recording.wav""#), "{}", body);
+        assert!(body.contains("RIFF fake wav"));
+    }
+
+    #[tokio::test]
+    async fn leaves_out_unset_options() {
+        let mock = MockBackend::start([MockResponse::Json(r#"{"text": "hi"}"#)]);
+        let provider = OpenAiCompatibleProvider::new(
+            OpenAiCompatibleConfig {
+                api_key: None,
+                language: None,
+                prompt: None,
+                temperature: None,
+                ..config(&mock)
+            },
+            reqwest::Client::new(),
+        );
+        let transcript = provider.transcribe(&audio()).await.unwrap();
+        assert_eq!(transcript.language, None);
+        assert_eq!(transcript.duration_seconds, None);
+
+        let request = &mock.requests()[0];
+        assert_eq!(request.authorization, None);
+        for field in ["language", "prompt", "temperature"] {
+            assert_eq!(request.form_field(field), None, "{}", field);
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_requested_language() {
+        let mock = MockBackend::start([MockResponse::Json(r#"{"text": "hi"}"#)]);
+        let provider = OpenAiCompatibleProvider::new(config(&mock), reqwest::Client::new());
+        let transcript = provider.transcribe(&audio()).await.unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+    }
+
+    #[tokio::test]
+    async fn reports_error_status_and_bad_json() {
+        let mock = MockBackend::start([MockResponse::Status(429), MockResponse::Malformed]);
+        let provider = OpenAiCompatibleProvider::new(config(&mock), reqwest::Client::new());
+
+        let err = provider.transcribe(&audio()).await.unwrap_err();
+        assert!(err.starts_with("openai-compatible error 429"), "{}", err);
+        assert!(err.contains("Mock status 429"), "{}", err);
+        let err = provider.transcribe(&audio()).await.unwrap_err();
+        assert!(err.starts_with("Failed to parse response"), "{}", err);
+    }
+}
@@ -0,0 +1,502 @@
+use super::config::BackendConfigStore;
+use super::health::check_backend_health;
+use super::http::app_client;
+use super::retry::AttemptError;
+use super::upload::AudioSource;
+use super::{
+    process_voice_recording, profile_for_app, save_detected_upload_format, VoiceProcessResponse,
+};
+use crate::app_profiles;
+use crate::auth::StoredTokenSource;
+use crate::context::VoiceContext;
+use crate::pipeline;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{debug, error, info, warn};
+
+const QUEUE_DIR: &str = "offline_queue";
+/// How often the background worker checks whether queued recordings can be sent
+const DRAIN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Distinguishes recordings queued within the same millisecond
+static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Whether a queued recording will be sent automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueueStatus {
+    /// Waiting for the backend to become reachable
+    Pending,
+    /// Rejected by the backend; only sent again when retried by hand
+    Failed,
+}
+
+/// A recording waiting to be processed - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedRecording {
+    pub id: String,
+    /// When the recording was queued, in milliseconds since the Unix epoch
+    pub created_at: u64,
+    /// Context captured when the recording was made
+    pub context: Option<VoiceContext>,
+    /// Name of the app profile that applied when the recording was made
+    #[serde(default)]
+    pub app_profile: Option<String>,
+    pub status: QueueStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+/// Sent to the UI as `offline-queue-completed` when a queued recording is processed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueCompletion {
+    pub id: String,
+    pub response: VoiceProcessResponse,
+}
+
+/// Recordings that could not be sent, stored as a WAV and a JSON file each
+#[derive(Debug, Default)]
+pub struct OfflineQueue {
+    dir: Option<PathBuf>,
+    /// Items currently being sent, so the worker and manual retries don't overlap
+    in_flight: HashSet<String>,
+}
+
+impl OfflineQueue {
+    /// Use the queue directory inside the given app data directory
+    pub fn load(app_dir: PathBuf) -> Self {
+        let dir = app_dir.join(QUEUE_DIR);
+        let queue = Self {
+            dir: Some(dir),
+            in_flight: HashSet::new(),
+        };
+        let pending = queue.list().len();
+        if pending > 0 {
+            info!("{} recordings waiting in the offline queue", pending);
+        }
+        queue
+    }
+
+    fn dir(&self) -> Result<&Path, String> {
+        self.dir
+            .as_deref()
+            .ok_or_else(|| "Offline queue not loaded".to_string())
+    }
+
+    fn metadata_path(&self, id: &str) -> Result<PathBuf, String> {
+        validate_id(id)?;
+        Ok(self.dir()?.join(format!("{}.json", id)))
+    }
+
+    fn audio_path(&self, id: &str) -> Result<PathBuf, String> {
+        validate_id(id)?;
+        Ok(self.dir()?.join(format!("{}.wav", id)))
+    }
+
+    /// Save a recording with the context and app profile it was made in
+    pub fn enqueue(
+        &self,
+        audio_data: &[u8],
+        context: Option<VoiceContext>,
+        app_profile: Option<&str>,
+        error: &str,
+    ) -> Result<QueuedRecording, String> {
+        let created_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let item = QueuedRecording {
+            id: format!(
+                "{}-{}",
+                created_at,
+                ID_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ),
+            created_at,
+            context,
+            app_profile: app_profile.map(str::to_string),
+            status: QueueStatus::Pending,
+            attempts: 1,
+            last_error: Some(error.to_string()),
+        };
+
+        std::fs::create_dir_all(self.dir()?)
+            .map_err(|e| format!("Failed to create offline queue dir: {}", e))?;
+        std::fs::write(self.audio_path(&item.id)?, audio_data)
+            .map_err(|e| format!("Failed to save queued audio: {}", e))?;
+        self.save(&item)?;
+
+        info!("Queued recording {} for later processing", item.id);
+        Ok(item)
+    }
+
+    fn save(&self, item: &QueuedRecording) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(item).map_err(|e| e.to_string())?;
+        std::fs::write(self.metadata_path(&item.id)?, content)
+            .map_err(|e| format!("Failed to save queued recording: {}", e))
+    }
+
+    /// All queued recordings, oldest first
+    pub fn list(&self) -> Vec<QueuedRecording> {
+        let Ok(entries) = self
+            .dir()
+            .and_then(|dir| std::fs::read_dir(dir).map_err(|e| e.to_string()))
+        else {
+            return Vec::new();
+        };
+
+        let mut items: Vec<QueuedRecording> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                match std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+                {
+                    Ok(item) => Some(item),
+                    Err(e) => {
+                        warn!("Skipping unreadable queue entry {:?}: {}", path, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        items.sort_by(|a: &QueuedRecording, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        items
+    }
+
+    pub fn get(&self, id: &str) -> Result<QueuedRecording, String> {
+        let content = std::fs::read_to_string(self.metadata_path(id)?)
+            .map_err(|_| format!("No queued recording '{}'", id))?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    /// The queued audio, streamed from disk when uploaded
+    pub fn audio(&self, id: &str) -> Result<AudioSource, String> {
+        let path = self.audio_path(id)?;
+        if !path.is_file() {
+            return Err(format!("Queued audio for '{}' is missing", id));
+        }
+        Ok(AudioSource::File(path))
+    }
+
+    /// Mark an item as being sent; false if it is already in flight
+    pub fn claim(&mut self, id: &str) -> bool {
+        self.in_flight.insert(id.to_string())
+    }
+
+    pub fn release(&mut self, id: &str) {
+        self.in_flight.remove(id);
+    }
+
+    /// Record a failed attempt; permanent failures stop automatic retries
+    pub fn record_failure(&self, id: &str, error: &str, permanent: bool) -> Result<(), String> {
+        let mut item = self.get(id)?;
+        item.attempts += 1;
+        item.last_error = Some(error.to_string());
+        item.status = if permanent {
+            QueueStatus::Failed
+        } else {
+            QueueStatus::Pending
+        };
+        self.save(&item)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<(), String> {
+        self.in_flight.remove(id);
+        let metadata = self.metadata_path(id)?;
+        if !metadata.exists() {
+            return Err(format!("No queued recording '{}'", id));
+        }
+        std::fs::remove_file(metadata)
+            .map_err(|e| format!("Failed to delete queued recording: {}", e))?;
+        if let Err(e) = std::fs::remove_file(self.audio_path(id)?) {
+            warn!("Failed to delete queued audio for {}: {}", id, e);
+        }
+        debug!("Removed recording {} from the offline queue", id);
+        Ok(())
+    }
+
+    /// Copy a queued recording's audio to another location
+    pub fn export(&self, id: &str, destination: &Path) -> Result<(), String> {
+        std::fs::copy(self.audio_path(id)?, destination)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy queued audio: {}", e))
+    }
+}
+
+/// Ids become file names, so only allow what `enqueue` generates
+fn validate_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Err(format!("Invalid queued recording id '{}'", id));
+    }
+    Ok(())
+}
+
+/// Send one queued recording, removing it and notifying the user on success
+async fn process_queued(app: &AppHandle, id: &str) -> Result<VoiceProcessResponse, AttemptError> {
+    let queue = app.state::<Mutex<OfflineQueue>>();
+    let (item, audio) = {
+        let mut queue = queue
+            .lock()
+            .map_err(|e| AttemptError::fatal(e.to_string()))?;
+        let item = queue.get(id).map_err(AttemptError::fatal)?;
+        let audio = queue.audio(id).map_err(AttemptError::fatal)?;
+        if !queue.claim(id) {
+            return Err(AttemptError::fatal(format!(
+                "Recording '{}' is already being sent",
+                id
+            )));
+        }
+        (item, audio)
+    };
+
+    // Use the app profile of the recording, not of whatever app has focus now
+    let app_profile = item
+        .app_profile
+        .as_deref()
+        .and_then(|name| app_profiles::named(app, name));
+    let profile = match profile_for_app(&app.state(), app_profile.as_ref()) {
+        Ok(profile) => profile,
+        Err(e) => {
+            release(app, id);
+            return Err(AttemptError::fatal(e));
+        }
+    };
+    let pipeline_name = app_profile.as_ref().and_then(|p| p.pipeline.as_deref());
+
+    let tokens = StoredTokenSource::new(app.clone());
+    let result = process_voice_recording(
+        &app_client(app),
+        audio,
+        item.context.clone(),
+        &profile,
+        Some(&tokens),
+        |_| {},
+    )
+    .await;
+    save_detected_upload_format(app, &profile);
+    let result = match result {
+        Ok(mut response) => {
+            let context = item.context.clone().unwrap_or_default();
+            pipeline::apply_to_response(app, &mut response, &context, pipeline_name).await;
+            Ok(response)
+        }
+        Err(error) => Err(error),
+    };
+    let mut queue = queue
+        .lock()
+        .map_err(|e| AttemptError::fatal(e.to_string()))?;
+    match result {
+        Ok(response) => {
+            queue.remove(id).map_err(AttemptError::fatal)?;
+            drop(queue);
+            deliver(app, id, &response);
+            Ok(response)
+        }
+        Err(error) => {
+            queue.release(id);
+            if let Err(e) = queue.record_failure(id, &error.message, !error.retryable) {
+                warn!("Failed to update queued recording {}: {}", id, e);
+            }
+            Err(error)
+        }
+    }
+}
+
+fn release(app: &AppHandle, id: &str) {
+    if let Ok(mut queue) = app.state::<Mutex<OfflineQueue>>().lock() {
+        queue.release(id);
+    }
+}
+
+/// Show the result as a notification rather than typing into whatever has focus now
+fn deliver(app: &AppHandle, id: &str, response: &VoiceProcessResponse) {
+    let text = response
+        .final_text
+        .clone()
+        .or_else(|| response.transcription.as_ref().map(|t| t.text.clone()))
+        .unwrap_or_default();
+
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("Queued dictation processed")
+        .body(&text)
+        .show()
+    {
+        warn!("Failed to show notification for {}: {}", id, e);
+    }
+
+    let completion = QueueCompletion {
+        id: id.to_string(),
+        response: response.clone(),
+    };
+    if let Err(e) = app.emit("offline-queue-completed", &completion) {
+        error!("Failed to emit offline queue completion: {}", e);
+    }
+}
+
+/// Send pending recordings oldest first, stopping at the first connectivity failure
+async fn drain(app: &AppHandle) {
+    let pending: Vec<String> = match app.state::<Mutex<OfflineQueue>>().lock() {
+        Ok(queue) => queue
+            .list()
+            .into_iter()
+            .filter(|item| item.status == QueueStatus::Pending)
+            .map(|item| item.id)
+            .collect(),
+        Err(e) => {
+            error!("Failed to lock offline queue: {}", e);
+            return;
+        }
+    };
+    if pending.is_empty() {
+        return;
+    }
+
+    let profile = match app.state::<Mutex<BackendConfigStore>>().lock() {
+        Ok(store) => store.active_profile(),
+        Err(e) => {
+            error!("Failed to lock backend config: {}", e);
+            return;
+        }
+    };
+    let tokens = StoredTokenSource::new(app.clone());
+    let health = check_backend_health(&app_client(app), &profile, Some(&tokens)).await;
+    if !health.is_usable() {
+        debug!(
+            "Backend still unusable, {} recordings stay queued: {:?}",
+            pending.len(),
+            health.error
+        );
+        return;
+    }
+
+    info!(
+        "Backend reachable, sending {} queued recordings",
+        pending.len()
+    );
+    for id in pending {
+        match process_queued(app, &id).await {
+            Ok(_) => info!("Processed queued recording {}", id),
+            Err(error) if error.retryable => {
+                warn!(
+                    "Backend unreachable again, pausing offline queue: {}",
+                    error
+                );
+                break;
+            }
+            Err(error) => warn!("Queued recording {} was rejected: {}", id, error),
+        }
+    }
+}
+
+/// Periodically retry queued recordings in the background
+pub fn spawn_drain_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            drain(&app).await;
+            tokio::time::sleep(DRAIN_INTERVAL).await;
+        }
+    });
+}
+
+/// List recordings waiting in the offline queue
+#[tauri::command]
+pub async fn list_queued_recordings(
+    queue: State<'_, Mutex<OfflineQueue>>,
+) -> Result<Vec<QueuedRecording>, String> {
+    let queue = queue.lock().map_err(|e| e.to_string())?;
+    Ok(queue.list())
+}
+
+/// Send a queued recording now; the result is also delivered as a notification
+#[tauri::command]
+pub async fn retry_queued_recording(
+    app: AppHandle,
+    id: String,
+) -> Result<VoiceProcessResponse, String> {
+    process_queued(&app, &id).await.map_err(String::from)
+}
+
+/// Drop a recording from the offline queue
+#[tauri::command]
+pub async fn delete_queued_recording(
+    id: String,
+    queue: State<'_, Mutex<OfflineQueue>>,
+) -> Result<(), String> {
+    let mut queue = queue.lock().map_err(|e| e.to_string())?;
+    queue.remove(&id)
+}
+
+/// Copy a queued recording's audio out to a file
+#[tauri::command]
+pub async fn export_queued_recording(
+    id: String,
+    destination: String,
+    queue: State<'_, Mutex<OfflineQueue>>,
+) -> Result<(), String> {
+    let queue = queue.lock().map_err(|e| e.to_string())?;
+    queue.export(&id, Path::new(&destination))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_queue(name: &str) -> OfflineQueue {
+        let dir =
+            std::env::temp_dir().join(format!("whisperme-queue-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        OfflineQueue::load(dir)
+    }
+
+    #[test]
+    fn queued_recordings_round_trip_through_disk() {
+        let mut queue = temp_queue("round-trip");
+        let first = queue.enqueue(b"first", None, None, "offline").unwrap();
+        let second = queue
+            .enqueue(
+                b"second",
+                Some(VoiceContext::default()),
+                Some("Mail"),
+                "offline",
+            )
+            .unwrap();
+
+        let ids: Vec<String> = queue.list().into_iter().map(|item| item.id).collect();
+        assert_eq!(ids, vec![first.id.clone(), second.id.clone()]);
+        let AudioSource::File(path) = queue.audio(&second.id).unwrap() else {
+            panic!("queued audio should be read from disk");
+        };
+        assert_eq!(std::fs::read(path).unwrap(), b"second");
+        assert_eq!(
+            queue.get(&second.id).unwrap().app_profile.as_deref(),
+            Some("Mail")
+        );
+
+        queue.record_failure(&first.id, "rejected", true).unwrap();
+        let first_item = queue.get(&first.id).unwrap();
+        assert_eq!(first_item.status, QueueStatus::Failed);
+        assert_eq!(first_item.attempts, 2);
+
+        queue.remove(&first.id).unwrap();
+        assert_eq!(queue.list().len(), 1);
+        assert!(queue.remove(&first.id).is_err());
+    }
+
+    #[test]
+    fn rejects_ids_that_are_not_queue_file_names() {
+        let queue = temp_queue("ids");
+        assert!(queue.get("../backend_settings").is_err());
+        assert!(queue.audio("").is_err());
+    }
+}
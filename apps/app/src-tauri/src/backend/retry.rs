@@ -0,0 +1,621 @@
+use lazy_static::lazy_static;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{debug, info, warn};
+
+/// Consecutive failed attempts before the circuit opens
+const CIRCUIT_FAILURE_THRESHOLD: u32 = 5;
+/// How long an open circuit rejects requests before letting a trial through
+const CIRCUIT_OPEN_DURATION: Duration = Duration::from_secs(30);
+
+lazy_static! {
+    /// One circuit breaker per backend base URL
+    static ref CIRCUIT_BREAKERS: Mutex<HashMap<String, CircuitBreaker>> = Mutex::new(HashMap::new());
+}
+
+/// How failed backend requests are retried - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Give up once this much time has passed since the first attempt
+    pub max_total_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 8_000,
+            max_total_secs: 120,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential delay before the given retry (1 = first retry), with equal jitter
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << retry.saturating_sub(1).min(32))
+            .min(self.max_backoff_ms);
+        let half = exponential / 2;
+        Duration::from_millis(half + random_u64() % (exponential - half + 1))
+    }
+
+    pub fn max_total(&self) -> Duration {
+        Duration::from_secs(self.max_total_secs)
+    }
+}
+
+/// Random number for jitter, without pulling in an RNG crate
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos()),
+    );
+    hasher.finish()
+}
+
+/// Status codes that mean the backend is temporarily unable to answer
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Parse a `Retry-After` header given either as seconds or as an HTTP date
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// Why a request failed and whether repeating it could help
+#[derive(Debug)]
+pub struct AttemptError {
+    pub message: String,
+    pub retryable: bool,
+    /// Server-requested delay from `Retry-After`
+    pub retry_after: Option<Duration>,
+    /// HTTP status when the backend answered with an error
+    pub status: Option<StatusCode>,
+    /// Whether the backend itself failed, rather than the request or something local;
+    /// these failures count towards opening the circuit
+    pub backend_failure: bool,
+}
+
+impl AttemptError {
+    /// An error that will not go away by trying again
+    pub fn fatal(message: String) -> Self {
+        Self {
+            message,
+            retryable: false,
+            retry_after: None,
+            status: None,
+            backend_failure: false,
+        }
+    }
+
+    /// An error worth retrying, optionally after a server-requested delay
+    pub fn transient(message: String, retry_after: Option<Duration>) -> Self {
+        Self {
+            message,
+            retryable: true,
+            retry_after,
+            status: None,
+            backend_failure: true,
+        }
+    }
+
+    /// A response that could not be read or understood; not retried
+    pub fn invalid_response(message: String) -> Self {
+        Self {
+            backend_failure: true,
+            ..Self::fatal(message)
+        }
+    }
+
+    /// Classify a failed send; only connection failures are retried, but timeouts
+    /// and other transport errors still count against the backend
+    pub fn from_send_error(error: reqwest::Error) -> Self {
+        let message = format!("Failed to send request: {}", error);
+        if error.is_connect() {
+            Self::transient(message, None)
+        } else {
+            Self::invalid_response(message)
+        }
+    }
+
+    /// Classify an unsuccessful response by status code and `Retry-After`
+    pub fn from_status(status: StatusCode, retry_after: Option<&str>, body: &str) -> Self {
+        let message = format!("Backend error {}: {}", status, body);
+        let error = if is_retryable_status(status) {
+            let retry_after = retry_after.and_then(|v| parse_retry_after(v, SystemTime::now()));
+            Self::transient(message, retry_after)
+        } else {
+            Self::fatal(message)
+        };
+        Self {
+            status: Some(status),
+            backend_failure: status.is_server_error() || is_retryable_status(status),
+            ..error
+        }
+    }
+}
+
+impl std::fmt::Display for AttemptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<AttemptError> for String {
+    fn from(error: AttemptError) -> Self {
+        error.message
+    }
+}
+
+/// Progress of a retried backend request - sent to the UI as `voice-processing-progress`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryProgress {
+    /// 1-based number of the attempt that is running or scheduled
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// Set while waiting before the next attempt
+    pub retry_in_ms: Option<u64>,
+    /// Error of the previous attempt when retrying
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CircuitState {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// The open period elapsed and a single trial request is in flight; its result
+    /// decides whether to close again. Other requests wait until it reports back,
+    /// or until `until` in case it never does.
+    HalfOpen {
+        until: Instant,
+    },
+}
+
+/// Fails fast while a backend keeps failing, then lets a single trial through
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    state: CircuitState,
+    failure_threshold: u32,
+    open_duration: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            state: CircuitState::Closed { failures: 0 },
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+        }
+    }
+
+    /// Whether a request may be sent now; otherwise how long until the next trial
+    pub fn check(&mut self, now: Instant) -> Result<(), Duration> {
+        match self.state {
+            CircuitState::Closed { .. } => Ok(()),
+            CircuitState::Open { until } | CircuitState::HalfOpen { until } if now < until => {
+                Err(until - now)
+            }
+            CircuitState::Open { .. } | CircuitState::HalfOpen { .. } => {
+                debug!("Circuit half-open, allowing a trial request");
+                self.state = CircuitState::HalfOpen {
+                    until: now + self.open_duration,
+                };
+                Ok(())
+            }
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        if self.state != (CircuitState::Closed { failures: 0 }) {
+            debug!("Circuit closed");
+        }
+        self.state = CircuitState::Closed { failures: 0 };
+    }
+
+    pub fn record_failure(&mut self, now: Instant) {
+        self.state = match self.state {
+            CircuitState::Closed { failures } if failures + 1 < self.failure_threshold => {
+                CircuitState::Closed {
+                    failures: failures + 1,
+                }
+            }
+            _ => {
+                warn!(
+                    "Circuit opened for {}s after repeated failures",
+                    self.open_duration.as_secs()
+                );
+                CircuitState::Open {
+                    until: now + self.open_duration,
+                }
+            }
+        };
+    }
+
+    /// The request never reached the backend, so let the next one be the trial
+    pub fn release_trial(&mut self, now: Instant) {
+        if let CircuitState::HalfOpen { .. } = self.state {
+            self.state = CircuitState::Open { until: now };
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        matches!(self.state, CircuitState::Open { .. })
+    }
+}
+
+fn with_breaker<T>(key: &str, f: impl FnOnce(&mut CircuitBreaker) -> T) -> T {
+    let mut breakers = CIRCUIT_BREAKERS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let breaker = breakers
+        .entry(key.to_string())
+        .or_insert_with(|| CircuitBreaker::new(CIRCUIT_FAILURE_THRESHOLD, CIRCUIT_OPEN_DURATION));
+    f(breaker)
+}
+
+/// Run `attempt` until it succeeds, fails permanently, or the policy gives up.
+/// `circuit` names the breaker shared by all requests to the same backend.
+/// The returned error is transient when the backend was unreachable throughout.
+pub async fn with_retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    circuit: &str,
+    mut attempt: F,
+    mut on_progress: impl FnMut(&RetryProgress),
+) -> Result<T, AttemptError>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T, AttemptError>>,
+{
+    let started = Instant::now();
+    let max_attempts = policy.max_attempts.max(1);
+    let mut number = 1;
+
+    loop {
+        if let Err(wait) = with_breaker(circuit, |b| b.check(Instant::now())) {
+            return Err(AttemptError::transient(
+                format!(
+                    "Backend is unavailable, not retrying for another {}s",
+                    wait.as_secs().max(1)
+                ),
+                Some(wait),
+            ));
+        }
+
+        on_progress(&RetryProgress {
+            attempt: number,
+            max_attempts,
+            retry_in_ms: None,
+            last_error: None,
+        });
+
+        let remaining = policy.max_total().saturating_sub(started.elapsed());
+        let result = match tokio::time::timeout(remaining, attempt(number)).await {
+            Ok(result) => result,
+            Err(_) => Err(AttemptError::transient(
+                format!("Backend request gave up after {}s", policy.max_total_secs),
+                None,
+            )),
+        };
+
+        let error = match result {
+            Ok(value) => {
+                with_breaker(circuit, |b| b.record_success());
+                return Ok(value);
+            }
+            Err(error) => error,
+        };
+
+        let circuit_open = with_breaker(circuit, |b| {
+            if error.backend_failure {
+                b.record_failure(Instant::now());
+            } else if error.status.is_some() {
+                // The backend answered, it just rejected this request
+                b.record_success();
+            } else {
+                b.release_trial(Instant::now());
+            }
+            b.is_open()
+        });
+        if !error.retryable {
+            return Err(error);
+        }
+        if number >= max_attempts || circuit_open {
+            warn!("Giving up after {} attempts: {}", number, error.message);
+            return Err(error);
+        }
+
+        let delay = error.retry_after.unwrap_or_else(|| policy.backoff(number));
+        if started.elapsed() + delay >= policy.max_total() {
+            warn!(
+                "Not retrying, next attempt in {:?} would exceed {}s: {}",
+                delay, policy.max_total_secs, error.message
+            );
+            return Err(error);
+        }
+
+        number += 1;
+        info!(
+            "Attempt failed ({}), retrying in {:?} ({}/{})",
+            error.message, delay, number, max_attempts
+        );
+        on_progress(&RetryProgress {
+            attempt: number,
+            max_attempts,
+            retry_in_ms: Some(delay.as_millis() as u64),
+            last_error: Some(error.message),
+        });
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 5,
+            max_total_secs: 5,
+        }
+    }
+
+    #[test]
+    fn backoff_grows_and_stays_within_bounds() {
+        let policy = RetryPolicy::default();
+        for retry in 1..=10 {
+            let exponential = (500u64 << (retry - 1)).min(8_000);
+            let delay = policy.backoff(retry).as_millis() as u64;
+            assert!(
+                delay >= exponential / 2 && delay <= exponential,
+                "{}",
+                delay
+            );
+        }
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        let now = httpdate::parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert_eq!(parse_retry_after(" 7 ", now), Some(Duration::from_secs(7)));
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:50:07 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn circuit_opens_after_threshold_and_allows_a_trial() {
+        let mut breaker = CircuitBreaker::new(2, Duration::from_secs(10));
+        let now = Instant::now();
+        breaker.record_failure(now);
+        assert!(breaker.check(now).is_ok());
+        breaker.record_failure(now);
+        assert!(breaker.check(now + Duration::from_secs(5)).is_err());
+
+        // Trial after the open period; another failure re-opens immediately
+        assert!(breaker.check(now + Duration::from_secs(10)).is_ok());
+        breaker.record_failure(now + Duration::from_secs(10));
+        assert!(breaker.is_open());
+
+        // Only one request is let through while the trial is running
+        assert!(breaker.check(now + Duration::from_secs(20)).is_ok());
+        assert!(breaker.check(now + Duration::from_secs(20)).is_err());
+        assert!(breaker.check(now + Duration::from_secs(25)).is_err());
+        breaker.record_success();
+        assert!(breaker.check(now + Duration::from_secs(20)).is_ok());
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn stalled_or_abandoned_trial_lets_another_through() {
+        let mut breaker = CircuitBreaker::new(1, Duration::from_secs(10));
+        let now = Instant::now();
+        breaker.record_failure(now);
+
+        // A trial that never reports back stops blocking after another open period
+        assert!(breaker.check(now + Duration::from_secs(10)).is_ok());
+        assert!(breaker.check(now + Duration::from_secs(19)).is_err());
+        assert!(breaker.check(now + Duration::from_secs(20)).is_ok());
+
+        // A trial that failed before reaching the backend hands over immediately
+        breaker.release_trial(now + Duration::from_secs(21));
+        assert!(breaker.check(now + Duration::from_secs(21)).is_ok());
+    }
+
+    #[test]
+    fn classifies_backend_failures() {
+        let status =
+            |code| AttemptError::from_status(StatusCode::from_u16(code).unwrap(), None, "");
+        assert!(status(500).backend_failure && !status(500).retryable);
+        assert!(status(503).backend_failure && status(503).retryable);
+        assert!(status(429).backend_failure);
+        assert!(!status(400).backend_failure);
+        assert!(!status(401).backend_failure);
+        assert!(AttemptError::invalid_response("bad json".to_string()).backend_failure);
+        assert!(!AttemptError::fatal("no audio".to_string()).backend_failure);
+    }
+
+    #[tokio::test]
+    async fn fatal_backend_failures_open_the_circuit() {
+        let policy = RetryPolicy {
+            max_attempts: 1,
+            ..fast_policy()
+        };
+        let circuit = "test://fatal-backend";
+        for _ in 0..CIRCUIT_FAILURE_THRESHOLD {
+            let error = with_retry::<(), _, _>(
+                &policy,
+                circuit,
+                |_| async { Err(AttemptError::invalid_response("timed out".to_string())) },
+                |_| {},
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(error.message, "timed out");
+        }
+
+        let calls = AtomicU32::new(0);
+        let error = with_retry::<(), _, _>(
+            &policy,
+            circuit,
+            |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async { Ok(()) }
+            },
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            error.message.starts_with("Backend is unavailable"),
+            "{}",
+            error
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn client_errors_do_not_open_the_circuit() {
+        let policy = RetryPolicy {
+            max_attempts: 1,
+            ..fast_policy()
+        };
+        let circuit = "test://client-errors";
+        for _ in 0..CIRCUIT_FAILURE_THRESHOLD + 1 {
+            let error = with_retry::<(), _, _>(
+                &policy,
+                circuit,
+                |_| async {
+                    Err(AttemptError::from_status(
+                        StatusCode::BAD_REQUEST,
+                        None,
+                        "bad",
+                    ))
+                },
+                |_| {},
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(error.status, Some(StatusCode::BAD_REQUEST));
+        }
+        assert!(!with_breaker(circuit, |b| b.is_open()));
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_until_success() {
+        let calls = AtomicU32::new(0);
+        let mut progress = Vec::new();
+        let result = with_retry(
+            &fast_policy(),
+            "test://transient",
+            |attempt| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt < 3 {
+                        Err(AttemptError::transient("503".to_string(), None))
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            |p| progress.push(p.clone()),
+        )
+        .await
+        .map_err(String::from);
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        let started: Vec<u32> = progress
+            .iter()
+            .filter(|p| p.retry_in_ms.is_none())
+            .map(|p| p.attempt)
+            .collect();
+        assert_eq!(started, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_fatal_errors() {
+        let calls = AtomicU32::new(0);
+        let result = with_retry::<(), _, _>(
+            &fast_policy(),
+            "test://fatal",
+            |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async { Err(AttemptError::fatal("400".to_string())) }
+            },
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(result.message, "400");
+        assert!(!result.retryable);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_retry_after_exceeds_total_time() {
+        let calls = AtomicU32::new(0);
+        let result = with_retry::<(), _, _>(
+            &fast_policy(),
+            "test://retry-after",
+            |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async {
+                    Err(AttemptError::transient(
+                        "429".to_string(),
+                        Some(Duration::from_secs(60)),
+                    ))
+                }
+            },
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(result.message, "429");
+        assert!(result.retryable);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
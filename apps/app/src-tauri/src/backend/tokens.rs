@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+/// Supplies the access token sent to the backend as a Bearer header
+#[async_trait]
+pub trait TokenSource: Send + Sync {
+    /// The current access token, if the user is signed in
+    fn access_token(&self) -> Option<String>;
+
+    /// Obtain a new access token after the backend refused `rejected`.
+    /// Returns `None` when the user has to sign in again.
+    async fn refresh(&self, rejected: Option<&str>) -> Option<String>;
+}
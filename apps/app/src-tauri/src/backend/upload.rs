@@ -0,0 +1,125 @@
+use crate::context::VoiceContext;
+use bytes::Bytes;
+use lazy_static::lazy_static;
+use reqwest::multipart::{Form, Part};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::info;
+
+lazy_static! {
+    /// Backends that rejected a multipart upload but accepted the legacy JSON body,
+    /// until the detected format is saved in their profile
+    static ref LEGACY_BACKENDS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// How recordings are sent to the backend - matches TypeScript interface
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UploadFormat {
+    /// Multipart, switching to JSON for servers that reject it with 415.
+    /// The switch is saved in the profile.
+    #[default]
+    Auto,
+    /// Binary audio part plus a JSON context part
+    Multipart,
+    /// Legacy JSON body with base64 audio
+    Json,
+}
+
+impl UploadFormat {
+    /// Whether to try a multipart upload first for the given backend
+    pub fn prefers_multipart(self, base_url: &str) -> bool {
+        match self {
+            UploadFormat::Multipart => true,
+            UploadFormat::Json => false,
+            UploadFormat::Auto => !is_legacy_backend(base_url),
+        }
+    }
+}
+
+/// Responses from servers that don't understand multipart bodies. A 400 or 422
+/// usually means the request itself was bad, so only 415 counts.
+pub fn is_multipart_rejection(status: StatusCode) -> bool {
+    status == StatusCode::UNSUPPORTED_MEDIA_TYPE
+}
+
+/// Whether this backend was found to accept only JSON uploads
+pub fn is_legacy_backend(base_url: &str) -> bool {
+    LEGACY_BACKENDS
+        .lock()
+        .map(|legacy| legacy.contains(base_url))
+        .unwrap_or(false)
+}
+
+/// Use the JSON body for this backend from now on
+pub fn remember_legacy_backend(base_url: &str) {
+    if let Ok(mut legacy) = LEGACY_BACKENDS.lock() {
+        if legacy.insert(base_url.to_string()) {
+            info!("Backend {} only accepts JSON uploads", base_url);
+        }
+    }
+}
+
+/// Recording to upload, kept in memory or streamed from a file
+#[derive(Debug, Clone)]
+pub enum AudioSource {
+    Memory(Bytes),
+    File(PathBuf),
+}
+
+impl From<Vec<u8>> for AudioSource {
+    fn from(audio_data: Vec<u8>) -> Self {
+        AudioSource::Memory(Bytes::from(audio_data))
+    }
+}
+
+impl AudioSource {
+    /// Audio as a multipart part; files are streamed rather than read up front
+    async fn part(&self) -> Result<Part, String> {
+        let part = match self {
+            AudioSource::Memory(bytes) => {
+                Part::stream_with_length(reqwest::Body::from(bytes.clone()), bytes.len() as u64)
+            }
+            AudioSource::File(path) => {
+                let file = tokio::fs::File::open(path)
+                    .await
+                    .map_err(|e| format!("Failed to open audio file {:?}: {}", path, e))?;
+                let length = file
+                    .metadata()
+                    .await
+                    .map_err(|e| format!("Failed to read audio file {:?}: {}", path, e))?
+                    .len();
+                Part::stream_with_length(reqwest::Body::from(file), length)
+            }
+        };
+        part.file_name("recording.wav")
+            .mime_str("audio/wav")
+            .map_err(|e| e.to_string())
+    }
+
+    /// The whole recording, for the legacy JSON body
+    pub async fn read(&self) -> Result<Bytes, String> {
+        match self {
+            AudioSource::Memory(bytes) => Ok(bytes.clone()),
+            AudioSource::File(path) => tokio::fs::read(path)
+                .await
+                .map(Bytes::from)
+                .map_err(|e| format!("Failed to read audio file {:?}: {}", path, e)),
+        }
+    }
+}
+
+/// Multipart body with `audio` as binary WAV and `context` as a JSON part
+pub async fn multipart_form(audio: &AudioSource, context: &VoiceContext) -> Result<Form, String> {
+    let context = serde_json::to_string(context).map_err(|e| e.to_string())?;
+    let context = Part::text(context)
+        .mime_str("application/json")
+        .map_err(|e| e.to_string())?;
+    Ok(Form::new()
+        .text("format", "wav")
+        .part("context", context)
+        .part("audio", audio.part().await?))
+}
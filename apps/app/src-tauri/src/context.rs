@@ -18,11 +18,17 @@ pub struct VoiceContext {
     pub field_label: Option<String>,
     pub is_password_field: bool,
     pub is_rich_editor: bool,
+    /// Plain text fields, search boxes and combo boxes that can't hold line breaks
+    #[serde(default)]
+    pub is_single_line: bool,
     pub keyboard_mode: String,
     pub input_shift_state: String,
     pub locale: String,
     pub content_mime_types: Option<Vec<String>>,
     pub ime_options: ImeOptions,
+    /// Product names and jargon from the personal dictionary, as a recognition hint
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vocabulary: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +62,7 @@ impl Default for VoiceContext {
             field_label: None,
             is_password_field: false,
             is_rich_editor: false,
+            is_single_line: false,
             keyboard_mode: "text".to_string(),
             input_shift_state: "unshifted".to_string(),
             locale: "en-US".to_string(),
@@ -65,6 +72,7 @@ impl Default for VoiceContext {
                 flag_no_enter_action: false,
                 flag_no_personalized_learning: false,
             },
+            vocabulary: Vec::new(),
         }
     }
 }
@@ -165,6 +173,7 @@ pub fn get_text_field_info() -> Result<VoiceContext, String> {
         if let Ok(role) = get_element_attribute_string(focused_element, "AXRole") {
             context.field_type = Some(determine_field_type(&role));
             context.is_password_field = role.contains("SecureTextField") || role.contains("Password");
+            context.is_single_line = role == "AXTextField" || role == "AXComboBox" || role.contains("SearchField");
         }
 
         // Try to get field title/label
@@ -264,10 +273,11 @@ pub fn gather_voice_context() -> VoiceContext {
     context
 }
 
-/// Tauri command to gather voice context
+/// Tauri command to gather voice context, filtered by the context policy
 #[tauri::command]
-pub async fn gather_context() -> Result<VoiceContext, String> {
-    let context = gather_voice_context();
+pub async fn gather_context(app: tauri::AppHandle) -> Result<VoiceContext, String> {
+    let mut context = gather_voice_context();
+    crate::context_policy::apply_for_app(&app, &mut context);
     debug!("Gathered context: package={}, field_type={:?}, text_length={}", 
            context.package_name, context.field_type, context.full_text.len());
     Ok(context)
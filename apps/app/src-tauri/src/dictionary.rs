@@ -0,0 +1,497 @@
+use crate::store::{JsonStore, Validate};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+const DICTIONARY_FILE: &str = "dictionary.json";
+/// Length of the vocabulary hint in characters. Whisper reads at most 224 prompt
+/// tokens, and terms like names often take a token every two or three characters.
+const MAX_HINT_CHARS: usize = 400;
+
+/// A correctly spelled term and the ways it gets misheard - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictionaryEntry {
+    /// Spelling to insert, e.g. `WhisperMe`; also sent as a vocabulary hint
+    pub term: String,
+    /// Variants replaced with `term`, e.g. `whisper me`
+    #[serde(default)]
+    pub misspellings: Vec<String>,
+}
+
+/// The user's personal dictionary - matches TypeScript interface
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dictionary {
+    pub entries: Vec<DictionaryEntry>,
+}
+
+/// Shared dictionaries may be a bare list of entries or a whole dictionary
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DictionaryFile {
+    Dictionary(Dictionary),
+    Entries(Vec<DictionaryEntry>),
+}
+
+/// Regex for `text` matching only whole words. Boundaries are only required next
+/// to word characters so terms like `C++` or `.NET` still match.
+fn whole_word(text: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    format!(
+        "{}{}{}",
+        if is_word(text.chars().next()) {
+            r"\b"
+        } else {
+            ""
+        },
+        regex::escape(text),
+        if is_word(text.chars().last()) {
+            r"\b"
+        } else {
+            ""
+        }
+    )
+}
+
+fn same_word(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+impl Dictionary {
+    /// Terms to pass to the backend or provider as a recognition hint, as many as
+    /// fit in a comma-separated hint of `MAX_HINT_CHARS`
+    pub fn vocabulary(&self) -> Vec<String> {
+        let mut length = 0;
+        self.entries
+            .iter()
+            .map(|entry| entry.term.trim())
+            .filter(|term| !term.is_empty())
+            .take_while(|term| {
+                length += term.chars().count() + ", ".len();
+                length <= MAX_HINT_CHARS
+            })
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn rules(&self) -> Vec<(Regex, &str)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                let term = entry.term.trim();
+                let mut variants: Vec<&str> = entry
+                    .misspellings
+                    .iter()
+                    .map(|variant| variant.trim())
+                    .filter(|variant| !variant.is_empty())
+                    .collect();
+                if term.is_empty() || variants.is_empty() {
+                    return None;
+                }
+                // Longest first so `whisper me app` wins over `whisper me`
+                variants.sort_by_key(|variant| std::cmp::Reverse(variant.len()));
+                let pattern = variants
+                    .iter()
+                    .map(|variant| whole_word(variant))
+                    .collect::<Vec<_>>()
+                    .join("|");
+                match RegexBuilder::new(&pattern).case_insensitive(true).build() {
+                    Ok(regex) => Some((regex, term)),
+                    Err(e) => {
+                        warn!("Skipping dictionary entry '{}': {}", term, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Replace misspellings with the term as spelled. The term itself is left alone in
+    /// other casings so entries like `Go` or `Swift` don't capitalize ordinary words.
+    pub fn apply(&self, text: &str) -> String {
+        self.rules()
+            .iter()
+            .fold(text.to_string(), |text, (regex, term)| {
+                regex.replace_all(&text, regex::NoExpand(term)).into_owned()
+            })
+    }
+
+    /// Add entries, merging misspellings into existing terms with the same spelling
+    pub fn merge(&mut self, entries: Vec<DictionaryEntry>) {
+        for entry in entries {
+            let existing = self
+                .entries
+                .iter_mut()
+                .find(|existing| same_word(&existing.term, &entry.term));
+            match existing {
+                Some(existing) => {
+                    for variant in entry.misspellings {
+                        if !existing
+                            .misspellings
+                            .iter()
+                            .any(|known| same_word(known, &variant))
+                        {
+                            existing.misspellings.push(variant);
+                        }
+                    }
+                }
+                None => self.entries.push(entry),
+            }
+        }
+    }
+}
+
+impl Validate for Dictionary {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(entry) = self.entries.iter().find(|e| e.term.trim().is_empty()) {
+            return Err(format!(
+                "Dictionary entry with misspellings {:?} has no term",
+                entry.misspellings
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Split CSV content into records of fields, handling quotes, doubled quotes and
+/// line breaks inside quotes. Each record comes with the line it starts on.
+fn parse_csv(content: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let (mut line, mut record_line) = (1, 1);
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut fields)));
+                line += 1;
+                record_line = line;
+            }
+            '\n' => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+    records
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Parse `term,misspellings` rows; misspellings are separated by semicolons
+fn from_csv(content: &str) -> Result<Vec<DictionaryEntry>, String> {
+    let mut entries = Vec::new();
+    for (line, fields) in parse_csv(content) {
+        if fields.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let term = fields[0].trim();
+        if line == 1 && term.eq_ignore_ascii_case("term") {
+            continue;
+        }
+        if term.is_empty() {
+            return Err(format!("Line {} has no term", line));
+        }
+        let misspellings = fields
+            .get(1)
+            .map(|field| {
+                field
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|variant| !variant.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        entries.push(DictionaryEntry {
+            term: term.to_string(),
+            misspellings,
+        });
+    }
+    Ok(entries)
+}
+
+fn to_csv(dictionary: &Dictionary) -> String {
+    let mut csv = String::from("term,misspellings\n");
+    for entry in &dictionary.entries {
+        csv.push_str(&csv_field(&entry.term));
+        csv.push(',');
+        csv.push_str(&csv_field(&entry.misspellings.join(";")));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+}
+
+/// The personal dictionary persisted as JSON in the app data directory
+#[derive(Debug, Default)]
+pub struct DictionaryStore {
+    store: JsonStore<Dictionary>,
+}
+
+impl DictionaryStore {
+    /// Load the saved dictionary from the given app data directory
+    pub fn load(app_dir: PathBuf) -> Self {
+        let store = JsonStore::<Dictionary>::load(app_dir.join(DICTIONARY_FILE));
+        info!("Loaded {} dictionary entries", store.get().entries.len());
+        Self { store }
+    }
+
+    pub fn dictionary(&self) -> &Dictionary {
+        self.store.get()
+    }
+
+    /// Replace the dictionary and write it to disk
+    pub fn set_dictionary(&mut self, dictionary: Dictionary) -> Result<(), String> {
+        self.store.set(dictionary)
+    }
+
+    /// Merge imported entries in, or replace everything with them, and save
+    pub fn import(&mut self, entries: Vec<DictionaryEntry>, replace: bool) -> Result<(), String> {
+        let mut dictionary = if replace {
+            Dictionary::default()
+        } else {
+            self.dictionary().clone()
+        };
+        dictionary.merge(entries);
+        self.set_dictionary(dictionary)
+    }
+}
+
+/// Read the entries of a shared `.csv` or `.json` dictionary file
+pub fn read_dictionary_file(source: &Path) -> Result<Vec<DictionaryEntry>, String> {
+    let content = std::fs::read_to_string(source)
+        .map_err(|e| format!("Failed to read {:?}: {}", source, e))?;
+    let entries = if is_csv(source) {
+        from_csv(&content)?
+    } else {
+        match serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid dictionary file {:?}: {}", source, e))?
+        {
+            DictionaryFile::Dictionary(dictionary) => dictionary.entries,
+            DictionaryFile::Entries(entries) => entries,
+        }
+    };
+    info!(
+        "Read {} dictionary entries from {:?}",
+        entries.len(),
+        source
+    );
+    Ok(entries)
+}
+
+/// Write the dictionary as `.csv` or `.json`, depending on the file extension
+pub fn write_dictionary_file(dictionary: &Dictionary, destination: &Path) -> Result<(), String> {
+    let content = if is_csv(destination) {
+        to_csv(dictionary)
+    } else {
+        serde_json::to_string_pretty(dictionary).map_err(|e| e.to_string())?
+    };
+    std::fs::write(destination, content)
+        .map_err(|e| format!("Failed to export dictionary to {:?}: {}", destination, e))
+}
+
+/// Get a copy of the dictionary for code that only has the app handle
+pub fn app_dictionary(app: &AppHandle) -> Dictionary {
+    match app.try_state::<Mutex<DictionaryStore>>() {
+        Some(store) => match store.lock() {
+            Ok(store) => store.dictionary().clone(),
+            Err(e) => {
+                warn!("Failed to lock dictionary: {}", e);
+                Dictionary::default()
+            }
+        },
+        None => Dictionary::default(),
+    }
+}
+
+/// Get the personal dictionary
+#[tauri::command]
+pub async fn get_dictionary(
+    store: State<'_, Mutex<DictionaryStore>>,
+) -> Result<Dictionary, String> {
+    let store = store.lock().map_err(|e| e.to_string())?;
+    Ok(store.dictionary().clone())
+}
+
+/// Replace and save the personal dictionary
+#[tauri::command]
+pub async fn set_dictionary(
+    dictionary: Dictionary,
+    store: State<'_, Mutex<DictionaryStore>>,
+) -> Result<(), String> {
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    store.set_dictionary(dictionary)
+}
+
+/// Import a shared `.csv` or `.json` dictionary, returning the result
+#[tauri::command]
+pub async fn import_dictionary(
+    source: String,
+    replace: Option<bool>,
+    store: State<'_, Mutex<DictionaryStore>>,
+) -> Result<Dictionary, String> {
+    let entries = read_dictionary_file(Path::new(&source))?;
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    store.import(entries, replace.unwrap_or(false))?;
+    Ok(store.dictionary().clone())
+}
+
+/// Export the dictionary to share with a team
+#[tauri::command]
+pub async fn export_dictionary(
+    destination: String,
+    store: State<'_, Mutex<DictionaryStore>>,
+) -> Result<(), String> {
+    let dictionary = store
+        .lock()
+        .map_err(|e| e.to_string())?
+        .dictionary()
+        .clone();
+    write_dictionary_file(&dictionary, Path::new(&destination))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(term: &str, misspellings: &[&str]) -> DictionaryEntry {
+        DictionaryEntry {
+            term: term.to_string(),
+            misspellings: misspellings.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn replaces_whole_words_ignoring_case() {
+        let dictionary = Dictionary {
+            entries: vec![
+                entry("WhisperMe", &["whisper me", "wisper me"]),
+                entry("Kubernetes", &["cooper netties"]),
+                entry("C++", &["c plus plus"]),
+            ],
+        };
+
+        assert_eq!(
+            dictionary.apply("Open Whisper Me and deploy to cooper Netties"),
+            "Open WhisperMe and deploy to Kubernetes"
+        );
+        assert_eq!(
+            dictionary.apply("I write C plus plus and c++"),
+            "I write C++ and c++"
+        );
+        // Only whole words are replaced
+        assert_eq!(dictionary.apply("whisper mentor"), "whisper mentor");
+    }
+
+    #[test]
+    fn leaves_the_term_itself_alone() {
+        let dictionary = Dictionary {
+            entries: vec![entry("Go", &["go lang"]), entry("Swift", &[])],
+        };
+        assert_eq!(
+            dictionary.apply("let's go write some go lang, swift and quick"),
+            "let's go write some Go, swift and quick"
+        );
+    }
+
+    #[test]
+    fn caps_the_vocabulary_hint_by_length() {
+        let dictionary = Dictionary {
+            entries: (0..200)
+                .map(|i| entry(&format!("Term{:03}", i), &[]))
+                .collect(),
+        };
+        let vocabulary = dictionary.vocabulary();
+        assert!(vocabulary.len() < 200);
+        assert!(vocabulary.join(", ").len() <= MAX_HINT_CHARS);
+        assert_eq!(vocabulary[0], "Term000");
+    }
+
+    #[test]
+    fn round_trips_through_csv() {
+        let dictionary = Dictionary {
+            entries: vec![
+                entry("Acme, Inc.", &["acme ink", "ackme"]),
+                entry("Jörg", &[]),
+                entry("Two\nlines", &["say \"two\"\r\nlines"]),
+                entry("Last", &["final"]),
+            ],
+        };
+        let parsed = from_csv(&to_csv(&dictionary)).unwrap();
+        assert_eq!(parsed, dictionary.entries);
+        assert_eq!(
+            from_csv("term,misspellings\r\nTauri,tory\r\n").unwrap(),
+            vec![entry("Tauri", &["tory"])]
+        );
+        assert_eq!(
+            from_csv("term,misspellings\n\"A\nB\",x\n,orphan\n"),
+            Err("Line 4 has no term".to_string())
+        );
+    }
+
+    #[test]
+    fn imports_and_merges_shared_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DictionaryStore::load(dir.path().to_path_buf());
+        store
+            .set_dictionary(Dictionary {
+                entries: vec![entry("WhisperMe", &["whisper me"])],
+            })
+            .unwrap();
+
+        let shared = dir.path().join("team.json");
+        std::fs::write(
+            &shared,
+            r#"[{"term": "whisperme", "misspellings": ["wisper me"]}, {"term": "Tauri"}]"#,
+        )
+        .unwrap();
+        store
+            .import(read_dictionary_file(&shared).unwrap(), false)
+            .unwrap();
+        assert_eq!(store.dictionary().entries.len(), 2);
+        assert_eq!(
+            store.dictionary().entries[0].misspellings,
+            vec!["whisper me", "wisper me"]
+        );
+
+        let exported = dir.path().join("export.csv");
+        write_dictionary_file(store.dictionary(), &exported).unwrap();
+        store
+            .import(read_dictionary_file(&exported).unwrap(), true)
+            .unwrap();
+        assert_eq!(store.dictionary().vocabulary(), vec!["WhisperMe", "Tauri"]);
+
+        let reloaded = DictionaryStore::load(dir.path().to_path_buf());
+        assert_eq!(reloaded.dictionary(), store.dictionary());
+    }
+}
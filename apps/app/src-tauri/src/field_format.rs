@@ -0,0 +1,367 @@
+//! Output rules for the kind of field the text goes into: addresses without spaces,
+//! digits for number fields, no trailing period in search boxes and no line breaks in
+//! single-line fields.
+
+use crate::context::VoiceContext;
+use crate::pipeline::itn;
+
+/// What kind of input the focused field takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    SingleLine,
+    Search,
+    Email,
+    Url,
+    Numeric,
+    Password,
+}
+
+impl FieldKind {
+    /// Classify the focused field from its type, keyboard mode and label
+    pub fn of(context: &VoiceContext) -> Self {
+        if context.is_password_field {
+            return FieldKind::Password;
+        }
+        let field_type = context.field_type.as_deref().unwrap_or_default();
+        for mode in [field_type, context.keyboard_mode.as_str()] {
+            match mode.to_lowercase().as_str() {
+                "password" => return FieldKind::Password,
+                "email" | "emailaddress" | "email_address" => return FieldKind::Email,
+                "url" | "uri" => return FieldKind::Url,
+                "number" | "numeric" | "decimal" | "phone" | "tel" => return FieldKind::Numeric,
+                "search" => return FieldKind::Search,
+                _ => {}
+            }
+        }
+        if context.ime_options.action == "search" {
+            return FieldKind::Search;
+        }
+
+        let described = [&context.field_label, &context.field_hint]
+            .into_iter()
+            .flatten()
+            .map(|text| text.to_lowercase())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if described.contains("email") || described.contains("e-mail") {
+            return FieldKind::Email;
+        }
+        if described
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| word == "url" || word == "website")
+        {
+            return FieldKind::Url;
+        }
+
+        if field_type == "combobox" || context.is_single_line {
+            FieldKind::SingleLine
+        } else {
+            FieldKind::Text
+        }
+    }
+
+    /// Whether the field holds prose that spacing and capitalization rules apply to
+    pub fn is_prose(self) -> bool {
+        matches!(
+            self,
+            FieldKind::Text | FieldKind::SingleLine | FieldKind::Search
+        )
+    }
+}
+
+/// Spoken names for address symbols
+const ADDRESS_WORDS: &[(&str, &str)] = &[
+    ("at", "@"),
+    ("dot", "."),
+    ("underscore", "_"),
+    ("dash", "-"),
+    ("hyphen", "-"),
+    ("plus", "+"),
+];
+const URL_WORDS: &[(&str, &str)] = &[("slash", "/"), ("colon", ":")];
+
+/// Lowercase, turn spoken symbols into characters and drop all spaces
+fn address(text: &str, url: bool) -> String {
+    let lower = text.to_lowercase();
+    let mut words = lower.split_whitespace().peekable();
+    let mut address = String::with_capacity(text.len());
+    while let Some(word) = words.next() {
+        // "at sign" is a common way to say @
+        if word == "at" && words.peek() == Some(&"sign") {
+            words.next();
+        }
+        let symbol = ADDRESS_WORDS
+            .iter()
+            .chain(if url { URL_WORDS } else { &[] })
+            .find(|(spoken, _)| *spoken == word)
+            .map(|(_, symbol)| *symbol);
+        address.push_str(symbol.unwrap_or(word));
+    }
+    address.trim_end_matches(['.', ',']).to_string()
+}
+
+fn digit_word(word: &str, locale: &str) -> Option<u64> {
+    match word {
+        "oh" => Some(0),
+        _ => itn::spoken_number(&[word], locale)
+            .map(|(value, _)| value)
+            .filter(|value| *value < 10),
+    }
+}
+
+/// Spoken numbers in the dictation locale as digits, keeping only characters a number
+/// field accepts. Runs of single digits, as in phone numbers, are read digit by digit.
+/// Text without any number is left as it was.
+fn numeric(text: &str, locale: &str) -> String {
+    let lower = text.to_lowercase();
+    // Split hyphenated number words like "twenty-five", but not "555-1234"
+    let words: Vec<&str> = lower
+        .split_whitespace()
+        .map(|word| word.trim_end_matches([',', '.']))
+        .flat_map(|word| {
+            let parts: Vec<&str> = word.split('-').collect();
+            if parts.len() > 1
+                && parts
+                    .iter()
+                    .all(|part| itn::spoken_number(&[part], locale).is_some())
+            {
+                parts
+            } else {
+                vec![word]
+            }
+        })
+        .collect();
+
+    let mut converted = String::new();
+    let mut i = 0;
+    while i < words.len() {
+        if matches!(words[i], "point" | "komma")
+            && words
+                .get(i + 1)
+                .is_some_and(|word| digit_word(word, locale).is_some())
+        {
+            converted.push('.');
+            i += 1;
+            while let Some(digit) = words.get(i).and_then(|word| digit_word(word, locale)) {
+                converted.push_str(&digit.to_string());
+                i += 1;
+            }
+            continue;
+        }
+        if words[i] == "oh" {
+            converted.push('0');
+            i += 1;
+            continue;
+        }
+        match itn::spoken_number(&words[i..], locale) {
+            Some((value, length)) => {
+                converted.push_str(&value.to_string());
+                i += length;
+            }
+            None => {
+                converted.push_str(if words[i] == "plus" { "+" } else { words[i] });
+                i += 1;
+            }
+        }
+    }
+
+    let kept: String = converted
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | '+' | '-'))
+        .collect();
+    if !kept.chars().any(|c| c.is_ascii_digit()) {
+        return text.to_string();
+    }
+    kept.trim_end_matches('.').to_string()
+}
+
+fn single_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Apply the output rules for a kind of field; number words are read in `locale`
+pub fn format_for_field(text: &str, kind: FieldKind, locale: &str) -> String {
+    match kind {
+        FieldKind::Text | FieldKind::Password => text.to_string(),
+        FieldKind::SingleLine => single_line(text),
+        FieldKind::Search => {
+            let line = single_line(text);
+            match line.strip_suffix('.') {
+                Some(stripped) if !stripped.ends_with('.') => stripped.to_string(),
+                _ => line,
+            }
+        }
+        FieldKind::Email => address(text, false),
+        FieldKind::Url => address(text, true),
+        FieldKind::Numeric => numeric(text, locale),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(field_type: Option<&str>, keyboard_mode: &str) -> VoiceContext {
+        VoiceContext {
+            field_type: field_type.map(str::to_string),
+            keyboard_mode: keyboard_mode.to_string(),
+            ..VoiceContext::default()
+        }
+    }
+
+    #[test]
+    fn classifies_fields() {
+        let cases = [
+            (context(Some("text"), "text"), FieldKind::Text),
+            (context(Some("search"), "text"), FieldKind::Search),
+            (context(Some("combobox"), "text"), FieldKind::SingleLine),
+            (context(Some("password"), "text"), FieldKind::Password),
+            (context(None, "email"), FieldKind::Email),
+            (context(None, "url"), FieldKind::Url),
+            (context(None, "number"), FieldKind::Numeric),
+            (context(None, "phone"), FieldKind::Numeric),
+        ];
+        for (context, expected) in cases {
+            assert_eq!(
+                FieldKind::of(&context),
+                expected,
+                "{:?}",
+                context.field_type
+            );
+        }
+
+        let mut labelled = context(Some("text"), "text");
+        labelled.field_label = Some("E-mail address".to_string());
+        assert_eq!(FieldKind::of(&labelled), FieldKind::Email);
+        labelled.field_label = Some("Website URL".to_string());
+        assert_eq!(FieldKind::of(&labelled), FieldKind::Url);
+
+        let mut single = context(Some("text"), "text");
+        single.is_single_line = true;
+        assert_eq!(FieldKind::of(&single), FieldKind::SingleLine);
+
+        let mut search = context(Some("text"), "text");
+        search.ime_options.action = "search".to_string();
+        assert_eq!(FieldKind::of(&search), FieldKind::Search);
+    }
+
+    #[test]
+    fn email_fields() {
+        let cases = [
+            (
+                "john dot smith at example dot com",
+                "john.smith@example.com",
+            ),
+            ("John.Smith at Example.com.", "john.smith@example.com"),
+            (
+                "jane underscore doe at sign mail dot org",
+                "jane_doe@mail.org",
+            ),
+            (
+                "first dash last plus news at x dot io",
+                "first-last+news@x.io",
+            ),
+            ("me@example.com", "me@example.com"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(format_for_field(text, FieldKind::Email, "en"), expected);
+        }
+    }
+
+    #[test]
+    fn url_fields() {
+        let cases = [
+            ("example dot com slash docs", "example.com/docs"),
+            (
+                "HTTPS colon slash slash Example dot com",
+                "https://example.com",
+            ),
+            ("www.example.com.", "www.example.com"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(format_for_field(text, FieldKind::Url, "en"), expected);
+        }
+    }
+
+    #[test]
+    fn numeric_fields() {
+        let cases = [
+            ("twenty five", "25"),
+            ("Twenty-five.", "25"),
+            ("one hundred and twelve", "112"),
+            ("three thousand four hundred", "3400"),
+            ("two million", "2000000"),
+            ("five five five one two three four", "5551234"),
+            ("oh seven", "07"),
+            ("three point one four", "3.14"),
+            ("12", "12"),
+            ("1,250", "1250"),
+            ("plus four nine", "+49"),
+            ("555-1234.", "555-1234"),
+            ("-5", "-5"),
+            ("ten", "10"),
+            ("twenty twenty four", "2024"),
+            ("nineteen eighty four", "1984"),
+            ("nineteen oh five", "1905"),
+            ("two thousand and five", "2005"),
+            ("twenty ten", "2010"),
+            ("no idea", "no idea"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(
+                format_for_field(text, FieldKind::Numeric, "en-US"),
+                expected,
+                "{:?}",
+                text
+            );
+        }
+
+        let german = [
+            ("fünfundzwanzig", "25"),
+            ("Zweitausendvierundzwanzig.", "2024"),
+            ("drei komma eins vier", "3.14"),
+            ("null sieben eins eins", "0711"),
+            ("keine Ahnung", "keine Ahnung"),
+        ];
+        for (text, expected) in german {
+            assert_eq!(
+                format_for_field(text, FieldKind::Numeric, "de-DE"),
+                expected,
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn search_fields() {
+        let cases = [
+            ("Best pizza near me.", "Best pizza near me"),
+            ("Is it open?", "Is it open?"),
+            ("Wait for it...", "Wait for it..."),
+            ("two\nlines.", "two lines"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(format_for_field(text, FieldKind::Search, "en"), expected);
+        }
+    }
+
+    #[test]
+    fn single_line_fields() {
+        assert_eq!(
+            format_for_field("Subject line\n\nmore text.\n", FieldKind::SingleLine, "en"),
+            "Subject line more text."
+        );
+    }
+
+    #[test]
+    fn text_fields_are_unchanged() {
+        let text = "Dear team,\n\nSee you at five.";
+        assert_eq!(format_for_field(text, FieldKind::Text, "en"), text);
+    }
+}
@@ -9,18 +9,54 @@ use accessibility::{is_macos_accessibility_enabled, open_apple_accessibility};
 pub mod recorder;
 pub mod overlay;
 pub mod context;
+pub mod context_policy;
 pub mod backend;
 pub mod auth;
+pub mod push_to_talk;
+pub mod pipeline;
+pub mod dictionary;
+pub mod voice_commands;
+pub mod app_profiles;
+pub mod smart_spacing;
+pub mod field_format;
+pub mod store;
 use recorder::commands::{
-    cancel_recording, close_recording_session, enumerate_recording_devices, get_recorder_state,
-    init_recording_session, start_recording, stop_recording, AppData,
+    cancel_recording, close_recording_session, enumerate_recording_devices,
+    get_device_input_settings, get_recorder_state, get_session_policy, get_sound_cue_settings,
+    init_recording_session, preview_sound_cue, set_session_policy, set_sound_cue_settings,
+    start_recording, stop_recording, AppData,
 };
 use overlay::{
-    hide_recording_overlay, show_processing_overlay, show_recording_overlay, 
-    stop_recording_from_overlay, OverlayManager,
+    cancel_processing_from_overlay, hide_recording_overlay, show_processing_overlay,
+    show_recording_overlay, stop_recording_from_overlay, OverlayManager,
 };
 use context::gather_context;
-use backend::{process_voice_with_backend, test_backend_connection};
+use context_policy::{get_context_policy, set_context_policy, ContextPolicyStore};
+use backend::{
+    get_backend_settings, process_voice_with_backend, select_backend_profile,
+    set_backend_settings, test_backend_connection, transcribe_with_provider,
+};
+use backend::config::BackendConfigStore;
+use backend::http::{get_http_settings, set_http_settings, HttpClient};
+use backend::jobs::{cancel_voice_processing, ProcessingJobs};
+use backend::queue::{
+    delete_queued_recording, export_queued_recording, list_queued_recordings,
+    retry_queued_recording, spawn_drain_worker, OfflineQueue,
+};
+use dictionary::{
+    export_dictionary, get_dictionary, import_dictionary, set_dictionary, DictionaryStore,
+};
+use pipeline::{get_pipeline_settings, run_text_pipeline, set_pipeline_settings, PipelineStore};
+use voice_commands::{
+    get_command_grammar, get_voice_command_settings, set_voice_command_settings,
+    VoiceCommandStore,
+};
+use app_profiles::{
+    get_active_app_profile, get_app_profiles, set_app_profiles, AppProfileStore, InsertionMethod,
+};
+use push_to_talk::{
+    get_push_to_talk_settings, register_push_to_talk, unregister_push_to_talk, PushToTalk,
+};
 use auth::{get_stored_tokens, store_tokens, clear_stored_tokens, start_oauth_server, stop_oauth_server, get_oauth_callback, listen_oauth_callback, handle_deep_link, handle_deep_link_with_app, js_log};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -38,7 +74,9 @@ pub fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_deep_link::init())
-        .manage(AppData::new());
+        .manage(AppData::new())
+        .manage(std::sync::Mutex::new(PushToTalk::new()))
+        .manage(std::sync::Mutex::new(ProcessingJobs::default()));
 
     // When a new instance is opened, focus on the main window if it's already running
     // https://v2.tauri.app/plugin/single-instance/#focusing-on-new-instance
@@ -60,21 +98,64 @@ pub fn run() {
         // Audio recorder commands
         get_recorder_state,
         enumerate_recording_devices,
+        get_device_input_settings,
         init_recording_session,
         close_recording_session,
         start_recording,
         stop_recording,
         cancel_recording,
+        get_session_policy,
+        set_session_policy,
+        // Sound cue commands
+        get_sound_cue_settings,
+        set_sound_cue_settings,
+        preview_sound_cue,
         // Overlay commands
         show_recording_overlay,
         show_processing_overlay,
         hide_recording_overlay,
         stop_recording_from_overlay,
+        cancel_processing_from_overlay,
+        // Native push-to-talk
+        register_push_to_talk,
+        unregister_push_to_talk,
+        get_push_to_talk_settings,
         // Context gathering
         gather_context,
+        get_context_policy,
+        set_context_policy,
         // Backend integration
         process_voice_with_backend,
+        cancel_voice_processing,
         test_backend_connection,
+        get_backend_settings,
+        set_backend_settings,
+        select_backend_profile,
+        transcribe_with_provider,
+        get_http_settings,
+        set_http_settings,
+        // Offline queue
+        list_queued_recordings,
+        retry_queued_recording,
+        delete_queued_recording,
+        export_queued_recording,
+        // Text pipeline
+        get_pipeline_settings,
+        set_pipeline_settings,
+        run_text_pipeline,
+        // Personal dictionary
+        get_dictionary,
+        set_dictionary,
+        import_dictionary,
+        export_dictionary,
+        // Spoken punctuation and editing commands
+        get_voice_command_settings,
+        set_voice_command_settings,
+        get_command_grammar,
+        // Per-app profiles
+        get_app_profiles,
+        set_app_profiles,
+        get_active_app_profile,
         // Auth commands
         get_stored_tokens,
         store_tokens,
@@ -94,21 +175,64 @@ pub fn run() {
         // Audio recorder commands
         get_recorder_state,
         enumerate_recording_devices,
+        get_device_input_settings,
         init_recording_session,
         close_recording_session,
         start_recording,
         stop_recording,
         cancel_recording,
+        get_session_policy,
+        set_session_policy,
+        // Sound cue commands
+        get_sound_cue_settings,
+        set_sound_cue_settings,
+        preview_sound_cue,
         // Overlay commands
         show_recording_overlay,
         show_processing_overlay,
         hide_recording_overlay,
         stop_recording_from_overlay,
+        cancel_processing_from_overlay,
+        // Native push-to-talk
+        register_push_to_talk,
+        unregister_push_to_talk,
+        get_push_to_talk_settings,
         // Context gathering
         gather_context,
+        get_context_policy,
+        set_context_policy,
         // Backend integration
         process_voice_with_backend,
+        cancel_voice_processing,
         test_backend_connection,
+        get_backend_settings,
+        set_backend_settings,
+        select_backend_profile,
+        transcribe_with_provider,
+        get_http_settings,
+        set_http_settings,
+        // Offline queue
+        list_queued_recordings,
+        retry_queued_recording,
+        delete_queued_recording,
+        export_queued_recording,
+        // Text pipeline
+        get_pipeline_settings,
+        set_pipeline_settings,
+        run_text_pipeline,
+        // Personal dictionary
+        get_dictionary,
+        set_dictionary,
+        import_dictionary,
+        export_dictionary,
+        // Spoken punctuation and editing commands
+        get_voice_command_settings,
+        set_voice_command_settings,
+        get_command_grammar,
+        // Per-app profiles
+        get_app_profiles,
+        set_app_profiles,
+        get_active_app_profile,
         // Auth commands
         get_stored_tokens,
         store_tokens,
@@ -126,6 +250,53 @@ pub fn run() {
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
 
+    // Limits on what field contents are sent along with recordings
+    app.manage(std::sync::Mutex::new(load_store(&app, ContextPolicyStore::load)));
+
+    // Backend endpoint profiles live in the app data dir too
+    app.manage(std::sync::Mutex::new(load_store(&app, BackendConfigStore::load)));
+
+    // One HTTP client with proxy and certificate settings for all backend and auth calls
+    app.manage(std::sync::Mutex::new(load_store(&app, HttpClient::load)));
+
+    // Recordings that couldn't reach the backend wait on disk until it is back
+    app.manage(std::sync::Mutex::new(load_store(&app, OfflineQueue::load)));
+    spawn_drain_worker(app.handle().clone());
+
+    // Text pipelines that post-process dictated text before insertion
+    app.manage(std::sync::Mutex::new(load_store(&app, PipelineStore::load)));
+
+    // Personal dictionary of names and jargon the recognizer gets wrong
+    app.manage(std::sync::Mutex::new(load_store(&app, DictionaryStore::load)));
+
+    // Spoken punctuation, layout and correction commands per language
+    app.manage(std::sync::Mutex::new(load_store(&app, VoiceCommandStore::load)));
+
+    // Per-app provider, language, pipeline and insertion choices
+    app.manage(std::sync::Mutex::new(load_store(&app, AppProfileStore::load)));
+
+    // Load saved per-device input, sound cue and session settings now that the app
+    // data dir is known, and forward stream errors from the audio thread to the frontend
+    if let Ok(mut audio_manager) = app.state::<AppData>().audio_manager.lock() {
+        if let Ok(app_dir) = app.path().app_data_dir() {
+            audio_manager.load_device_settings(app_dir.clone());
+            audio_manager.load_sound_cue_settings(app_dir.clone());
+            audio_manager.load_session_policy(app_dir);
+        }
+        let app_handle = app.handle().clone();
+        audio_manager.set_stream_error_handler(move |event| {
+            let _ = app_handle.emit("recorder-stream-error", event);
+        });
+    }
+
+    // Register the saved dictation shortcut, if push-to-talk was set up before
+    if let Ok(mut push_to_talk) = app.state::<std::sync::Mutex<PushToTalk>>().lock() {
+        if let Ok(app_dir) = app.path().app_data_dir() {
+            push_to_talk.load(app_dir);
+        }
+        push_to_talk.restore(app.handle());
+    }
+
     // Initialize overlay manager after app is created
     let overlay_manager = std::sync::Mutex::new(OverlayManager::new(app.handle().clone()));
     app.manage(overlay_manager);
@@ -139,12 +310,73 @@ pub fn run() {
     });
 }
 
-use enigo::{Enigo, Keyboard, Settings};
-use tauri::Manager;
+/// Load a store from the app data dir, or keep it in memory when there is none
+fn load_store<S: Default>(app: &tauri::App, load: impl FnOnce(std::path::PathBuf) -> S) -> S {
+    match app.path().app_data_dir() {
+        Ok(app_dir) => load(app_dir),
+        Err(_) => S::default(),
+    }
+}
 
-/// Write text to the active application using the Enigo library
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use tauri::{Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Write text to the active application using the Enigo library, or by pasting it
+/// when the app profile for `package_name`, or else the frontmost app, asks for that.
+/// Text from a cancelled processing job is dropped.
 #[tauri::command]
-fn write_text(text: String) -> Result<(), String> {
-    let mut enigo = Enigo::new(&Settings::default()).unwrap();
-    enigo.text(&text).map_err(|e| e.to_string())
+async fn write_text(
+    app: tauri::AppHandle,
+    text: String,
+    job_id: Option<String>,
+    package_name: Option<String>,
+    jobs: tauri::State<'_, std::sync::Mutex<ProcessingJobs>>,
+) -> Result<(), String> {
+    if let Some(job_id) = job_id {
+        if jobs.lock().map_err(|e| e.to_string())?.is_cancelled(&job_id) {
+            tracing::info!("Not inserting text from cancelled job {}", job_id);
+            return Ok(());
+        }
+    }
+    // Looking up the window, typing and waiting for a paste all block
+    tauri::async_runtime::spawn_blocking(move || {
+        let insertion =
+            app_profiles::resolve_for_app(&app, package_name.as_deref().unwrap_or_default())
+                .and_then(|profile| profile.insertion)
+                .unwrap_or_default();
+        let mut enigo = Enigo::new(&Settings::default()).map_err(|e| e.to_string())?;
+        match insertion {
+            InsertionMethod::Type => enigo.text(&text).map_err(|e| e.to_string()),
+            InsertionMethod::Paste => paste_text(&app, &mut enigo, &text),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Paste text through the clipboard, putting back what was there before
+fn paste_text(app: &tauri::AppHandle, enigo: &mut Enigo, text: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let modifier = Key::Meta;
+    #[cfg(not(target_os = "macos"))]
+    let modifier = Key::Control;
+
+    let previous = app.clipboard().read_text().ok();
+    app.clipboard()
+        .write_text(text.to_string())
+        .map_err(|e| e.to_string())?;
+    enigo.key(modifier, Direction::Press).map_err(|e| e.to_string())?;
+    let pasted = enigo.key(Key::Unicode('v'), Direction::Click);
+    enigo.key(modifier, Direction::Release).map_err(|e| e.to_string())?;
+    pasted.map_err(|e| e.to_string())?;
+
+    // Give the target app time to read the clipboard before restoring it
+    std::thread::sleep(std::time::Duration::from_millis(150));
+    if let Some(previous) = previous {
+        if let Err(e) = app.clipboard().write_text(previous) {
+            tracing::warn!("Failed to restore clipboard: {}", e);
+        }
+    }
+    Ok(())
 }
@@ -1,3 +1,4 @@
+use crate::backend::jobs::{emit_cancelled, ProcessingJobs};
 use tauri::{AppHandle, Emitter, Manager, WebviewWindow, WebviewWindowBuilder, WindowEvent};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info};
@@ -178,4 +179,23 @@ pub async fn stop_recording_from_overlay(
     }
     
     Ok(())
-} 
\ No newline at end of file
+} 
+
+/// Cancel voice processing when the user clicks the overlay in its Processing state
+#[tauri::command]
+pub async fn cancel_processing_from_overlay(
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    info!("Processing cancellation requested from overlay");
+
+    let cancelled = {
+        let jobs = app_handle.state::<std::sync::Mutex<ProcessingJobs>>();
+        let mut jobs = jobs.lock().map_err(|e| e.to_string())?;
+        jobs.cancel_all()
+    };
+    emit_cancelled(&app_handle, &cancelled);
+
+    let overlay = app_handle.state::<std::sync::Mutex<OverlayManager>>();
+    let mut overlay = overlay.lock().map_err(|e| e.to_string())?;
+    overlay.hide_overlay().map_err(|e| e.to_string())
+}
@@ -0,0 +1,1375 @@
+//! Inverse text normalization: spoken numbers, money, percentages, times, dates and
+//! units written the way people type them, so "twenty five dollars on march third at
+//! three thirty pm" becomes "$25 on March 3 at 3:30 PM".
+//!
+//! Standalone numbers and ordinals below ten stay words, as in "two cats" or "first".
+
+use tracing::debug;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    English,
+    German,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Locale {
+    language: Language,
+    /// Whether "pounds" means money rather than weight
+    british: bool,
+}
+
+impl Locale {
+    fn parse(locale: &str) -> Option<Self> {
+        let locale = locale.to_lowercase();
+        let mut parts = locale.split(['-', '_']);
+        let language = match parts.next()? {
+            "en" => Language::English,
+            "de" => Language::German,
+            _ => return None,
+        };
+        let british = matches!(parts.next(), Some("gb" | "uk"));
+        Some(Self { language, british })
+    }
+
+    fn decimal_separator(self) -> char {
+        match self.language {
+            Language::English => '.',
+            Language::German => ',',
+        }
+    }
+
+    fn group_separator(self) -> char {
+        match self.language {
+            Language::English => ',',
+            Language::German => '.',
+        }
+    }
+}
+
+/// Whether inverse text normalization knows the language of a locale like `en-US`
+pub fn supports(locale: &str) -> bool {
+    Locale::parse(locale).is_some()
+}
+
+/// Unit names, one list of accepted words per position, and their symbols
+type UnitTable = &'static [(&'static [&'static [&'static str]], &'static str)];
+
+const ENGLISH_UNITS: UnitTable = &[
+    (
+        &[
+            &["kilometer", "kilometers", "kilometre", "kilometres"],
+            &["per", "an"],
+            &["hour"],
+        ],
+        "km/h",
+    ),
+    (&[&["mile", "miles"], &["per", "an"], &["hour"]], "mph"),
+    (&[&["degree", "degrees"], &["celsius", "centigrade"]], "°C"),
+    (&[&["degree", "degrees"], &["fahrenheit"]], "°F"),
+    (
+        &[&["kilometer", "kilometers", "kilometre", "kilometres"]],
+        "km",
+    ),
+    (
+        &[&["centimeter", "centimeters", "centimetre", "centimetres"]],
+        "cm",
+    ),
+    (
+        &[&["millimeter", "millimeters", "millimetre", "millimetres"]],
+        "mm",
+    ),
+    (&[&["meter", "meters", "metre", "metres"]], "m"),
+    (&[&["mile", "miles"]], "mi"),
+    (&[&["foot", "feet"]], "ft"),
+    (&[&["inch", "inches"]], "in"),
+    (&[&["kilogram", "kilograms", "kilo", "kilos"]], "kg"),
+    (&[&["gram", "grams"]], "g"),
+    (&[&["pound", "pounds"]], "lb"),
+    (&[&["ounce", "ounces"]], "oz"),
+    (
+        &[&["milliliter", "milliliters", "millilitre", "millilitres"]],
+        "ml",
+    ),
+    (&[&["liter", "liters", "litre", "litres"]], "l"),
+    (&[&["degree", "degrees"]], "°"),
+    (&[&["kilobyte", "kilobytes"]], "KB"),
+    (&[&["megabyte", "megabytes"]], "MB"),
+    (&[&["gigabyte", "gigabytes"]], "GB"),
+    (&[&["terabyte", "terabytes"]], "TB"),
+];
+
+const GERMAN_UNITS: UnitTable = &[
+    (&[&["kilometer"], &["pro"], &["stunde"]], "km/h"),
+    (&[&["stundenkilometer"]], "km/h"),
+    (&[&["grad"], &["celsius"]], "°C"),
+    (&[&["grad"], &["fahrenheit"]], "°F"),
+    (&[&["kilometer", "kilometern"]], "km"),
+    (&[&["zentimeter", "zentimetern"]], "cm"),
+    (&[&["millimeter", "millimetern"]], "mm"),
+    (&[&["meter", "metern"]], "m"),
+    (&[&["kilogramm", "kilo"]], "kg"),
+    (&[&["gramm"]], "g"),
+    (&[&["milliliter", "millilitern"]], "ml"),
+    (&[&["liter", "litern"]], "l"),
+    (&[&["grad"]], "°"),
+    (&[&["kilobyte"]], "KB"),
+    (&[&["megabyte"]], "MB"),
+    (&[&["gigabyte"]], "GB"),
+    (&[&["terabyte"]], "TB"),
+];
+
+const ENGLISH_MONTHS: &[(&str, &str)] = &[
+    ("january", "January"),
+    ("february", "February"),
+    ("march", "March"),
+    ("april", "April"),
+    ("may", "May"),
+    ("june", "June"),
+    ("july", "July"),
+    ("august", "August"),
+    ("september", "September"),
+    ("october", "October"),
+    ("november", "November"),
+    ("december", "December"),
+];
+
+const GERMAN_MONTHS: &[(&str, &str)] = &[
+    ("januar", "Januar"),
+    ("februar", "Februar"),
+    ("märz", "März"),
+    ("april", "April"),
+    ("mai", "Mai"),
+    ("juni", "Juni"),
+    ("juli", "Juli"),
+    ("august", "August"),
+    ("september", "September"),
+    ("oktober", "Oktober"),
+    ("november", "November"),
+    ("dezember", "Dezember"),
+];
+
+/// Days in each month in calendar order, allowing February 29
+const MONTH_DAYS: [u64; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// Names and idioms where an ordinal stays a word
+const ORDINAL_PHRASES: &[&[&str]] = &[&["twelfth", "night"], &["eleventh", "hour"]];
+
+/// A part of an English number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Term {
+    Unit(u64),
+    Teen(u64),
+    Tens(u64),
+    Hundred,
+    Scale(u64),
+}
+
+fn english_term(word: &str) -> Option<Term> {
+    let term = match word {
+        "one" => Term::Unit(1),
+        "two" => Term::Unit(2),
+        "three" => Term::Unit(3),
+        "four" => Term::Unit(4),
+        "five" => Term::Unit(5),
+        "six" => Term::Unit(6),
+        "seven" => Term::Unit(7),
+        "eight" => Term::Unit(8),
+        "nine" => Term::Unit(9),
+        "ten" => Term::Teen(10),
+        "eleven" => Term::Teen(11),
+        "twelve" => Term::Teen(12),
+        "thirteen" => Term::Teen(13),
+        "fourteen" => Term::Teen(14),
+        "fifteen" => Term::Teen(15),
+        "sixteen" => Term::Teen(16),
+        "seventeen" => Term::Teen(17),
+        "eighteen" => Term::Teen(18),
+        "nineteen" => Term::Teen(19),
+        "twenty" => Term::Tens(20),
+        "thirty" => Term::Tens(30),
+        "forty" => Term::Tens(40),
+        "fifty" => Term::Tens(50),
+        "sixty" => Term::Tens(60),
+        "seventy" => Term::Tens(70),
+        "eighty" => Term::Tens(80),
+        "ninety" => Term::Tens(90),
+        "hundred" => Term::Hundred,
+        "thousand" => Term::Scale(1_000),
+        "million" => Term::Scale(1_000_000),
+        "billion" => Term::Scale(1_000_000_000),
+        _ => return None,
+    };
+    Some(term)
+}
+
+fn english_ordinal_term(word: &str) -> Option<Term> {
+    let term = match word {
+        "first" => Term::Unit(1),
+        "second" => Term::Unit(2),
+        "third" => Term::Unit(3),
+        "fifth" => Term::Unit(5),
+        "eighth" => Term::Unit(8),
+        "ninth" => Term::Unit(9),
+        "twelfth" => Term::Teen(12),
+        _ => match word.strip_suffix("ieth") {
+            // twentieth, thirtieth, ...
+            Some(stem) => english_term(&format!("{}y", stem))?,
+            None => english_term(word.strip_suffix("th")?)?,
+        },
+    };
+    Some(term)
+}
+
+fn english_digit(word: &str) -> Option<u64> {
+    match word {
+        "zero" | "oh" => Some(0),
+        _ => match english_term(word)? {
+            Term::Unit(digit) => Some(digit),
+            _ => None,
+        },
+    }
+}
+
+fn english_suffix(value: u64) -> &'static str {
+    if (11..=13).contains(&(value % 100)) {
+        return "th";
+    }
+    match value % 10 {
+        1 => "st",
+        2 => "nd",
+        3 => "rd",
+        _ => "th",
+    }
+}
+
+fn german_below_hundred(word: &str) -> Option<u64> {
+    let value = match word {
+        "null" => 0,
+        "ein" | "eins" | "eine" => 1,
+        "zwei" => 2,
+        "drei" => 3,
+        "vier" => 4,
+        "fünf" => 5,
+        "sechs" => 6,
+        "sieben" => 7,
+        "acht" => 8,
+        "neun" => 9,
+        "zehn" => 10,
+        "elf" => 11,
+        "zwölf" => 12,
+        "dreizehn" => 13,
+        "vierzehn" => 14,
+        "fünfzehn" => 15,
+        "sechzehn" => 16,
+        "siebzehn" => 17,
+        "achtzehn" => 18,
+        "neunzehn" => 19,
+        "zwanzig" => 20,
+        "dreißig" => 30,
+        "vierzig" => 40,
+        "fünfzig" => 50,
+        "sechzig" => 60,
+        "siebzig" => 70,
+        "achtzig" => 80,
+        "neunzig" => 90,
+        _ => {
+            // einundzwanzig, zweiundvierzig, ...
+            let (unit, tens) = word.split_once("und")?;
+            let unit = german_below_hundred(unit).filter(|unit| (1..10).contains(unit))?;
+            let tens = german_below_hundred(tens).filter(|tens| tens % 10 == 0 && *tens >= 20)?;
+            return Some(unit + tens);
+        }
+    };
+    Some(value)
+}
+
+fn german_below_thousand(word: &str) -> Option<u64> {
+    match word.split_once("hundert") {
+        Some((hundreds, rest)) => {
+            let hundreds = match hundreds {
+                "" => 1,
+                _ => german_below_hundred(hundreds).filter(|value| *value > 0)?,
+            };
+            let rest = match rest {
+                "" => 0,
+                _ => german_below_hundred(rest)?,
+            };
+            Some(hundreds * 100 + rest)
+        }
+        None => german_below_hundred(word),
+    }
+}
+
+/// Value of a German number written as one word, like "zweitausendvierundzwanzig"
+fn german_compound(word: &str) -> Option<u64> {
+    match word.split_once("tausend") {
+        Some((thousands, rest)) => {
+            let thousands = match thousands {
+                "" => 1,
+                _ => german_below_thousand(thousands).filter(|value| *value > 0)?,
+            };
+            let rest = match rest {
+                "" => 0,
+                _ => german_below_thousand(rest)?,
+            };
+            Some(thousands * 1_000 + rest)
+        }
+        None => german_below_thousand(word),
+    }
+}
+
+fn german_scale(word: &str) -> Option<u64> {
+    match word {
+        "million" | "millionen" => Some(1_000_000),
+        "milliarde" | "milliarden" => Some(1_000_000_000),
+        _ => None,
+    }
+}
+
+/// Value of an inflected German ordinal like "dritten" or "einundzwanzigste"
+fn german_ordinal(word: &str) -> Option<u64> {
+    const ENDINGS: &[&str] = &[
+        "sten", "ster", "stes", "stem", "ste", "ten", "ter", "tes", "tem", "te",
+    ];
+    ENDINGS.iter().find_map(|ending| {
+        let stem = word.strip_suffix(ending)?;
+        match stem {
+            "er" => Some(1),
+            "drit" => Some(3),
+            "sech" => Some(6),
+            "sieb" => Some(7),
+            "ach" => Some(8),
+            _ => german_compound(stem).filter(|value| *value > 0),
+        }
+    })
+}
+
+/// A whitespace-separated token split into surrounding punctuation and the word itself
+#[derive(Debug)]
+struct Word {
+    lead: String,
+    core: String,
+    /// Lowercased core used for matching
+    lower: String,
+    trail: String,
+    /// Whitespace up to the next word
+    sep: String,
+}
+
+impl Word {
+    fn new(lead: &str, core: &str, trail: &str, sep: &str) -> Self {
+        Self {
+            lead: lead.to_string(),
+            core: core.to_string(),
+            lower: core.to_lowercase().replace('’', "'"),
+            trail: trail.to_string(),
+            sep: sep.to_string(),
+        }
+    }
+
+    fn original(&self) -> String {
+        format!("{}{}{}", self.lead, self.core, self.trail)
+    }
+}
+
+fn is_number_part(word: &str, language: Language) -> bool {
+    match language {
+        Language::English => english_term(word).is_some() || english_ordinal_term(word).is_some(),
+        Language::German => german_compound(word).is_some() || german_ordinal(word).is_some(),
+    }
+}
+
+fn split_words(text: &str, language: Language) -> (String, Vec<Word>) {
+    let start = text.len() - text.trim_start().len();
+    let mut rest = &text[start..];
+    let mut words = Vec::new();
+    while !rest.is_empty() {
+        let token_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (token, after) = rest.split_at(token_end);
+        let sep_end = after
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(after.len());
+        let (sep, after) = after.split_at(sep_end);
+        rest = after;
+
+        let Some(core_start) = token.find(char::is_alphanumeric) else {
+            words.push(Word::new(token, "", "", sep));
+            continue;
+        };
+        let core_end = token
+            .rfind(char::is_alphanumeric)
+            .map(|i| i + token[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(token.len());
+        // Keep the closing dot of "a.m." and "p.m." with the word
+        let core_end = match token[core_start..core_end].to_lowercase().as_str() {
+            "a.m" | "p.m" if token[core_end..].starts_with('.') => core_end + 1,
+            _ => core_end,
+        };
+        let (lead, core, trail) = (
+            &token[..core_start],
+            &token[core_start..core_end],
+            &token[core_end..],
+        );
+
+        // "twenty-five" reads like "twenty five"
+        let parts: Vec<&str> = core.split('-').collect();
+        if parts.len() > 1
+            && parts
+                .iter()
+                .all(|part| is_number_part(&part.to_lowercase(), language))
+        {
+            let last = parts.len() - 1;
+            for (i, part) in parts.iter().enumerate() {
+                let lead = if i == 0 { lead } else { "" };
+                let (trail, sep) = if i == last { (trail, sep) } else { ("", "-") };
+                words.push(Word::new(lead, part, trail, sep));
+            }
+        } else {
+            words.push(Word::new(lead, core, trail, sep));
+        }
+    }
+    (text[..start].to_string(), words)
+}
+
+/// A number read from words or digits
+#[derive(Debug, Clone, PartialEq)]
+struct Number {
+    negative: bool,
+    int: u64,
+    fraction: String,
+    /// Said as words rather than written as digits
+    spoken: bool,
+}
+
+impl Number {
+    fn whole(&self) -> Option<u64> {
+        (!self.negative && self.fraction.is_empty()).then_some(self.int)
+    }
+}
+
+struct Normalizer<'a> {
+    locale: Locale,
+    words: &'a [Word],
+}
+
+impl Normalizer<'_> {
+    fn word(&self, i: usize) -> Option<&str> {
+        self.words.get(i).map(|word| word.lower.as_str())
+    }
+
+    /// Whether the word at `i` continues the phrase ending before it, with no
+    /// punctuation or line break in between
+    fn follows(&self, i: usize) -> bool {
+        i > 0
+            && i < self.words.len()
+            && self.words[i - 1].trail.is_empty()
+            && self.words[i].lead.is_empty()
+            && !self.words[i - 1].sep.contains('\n')
+    }
+
+    /// The word at `i` if it continues the phrase
+    fn next(&self, i: usize) -> Option<&str> {
+        self.follows(i).then(|| self.word(i)).flatten()
+    }
+
+    fn english(&self) -> bool {
+        self.locale.language == Language::English
+    }
+
+    fn group(&self, int: u64) -> String {
+        let digits = int.to_string();
+        if int < 10_000 {
+            return digits;
+        }
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(self.locale.group_separator());
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    fn format_number(&self, number: &Number) -> String {
+        let mut text = String::new();
+        if number.negative {
+            text.push('-');
+        }
+        text.push_str(&self.group(number.int));
+        if !number.fraction.is_empty() {
+            text.push(self.locale.decimal_separator());
+            text.push_str(&number.fraction);
+        }
+        text
+    }
+
+    /// A number already written with digits, like `1,250` or `3.5`
+    fn digits(&self, i: usize) -> Option<Number> {
+        let core = &self.words.get(i)?.core;
+        let (int, fraction) = core
+            .split_once(self.locale.decimal_separator())
+            .unwrap_or((core, ""));
+        if !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let groups: Vec<&str> = int.split(self.locale.group_separator()).collect();
+        let grouped = groups.len() > 1
+            && (1..=3).contains(&groups[0].len())
+            && groups[1..].iter().all(|group| group.len() == 3);
+        if groups.len() > 1 && !grouped {
+            return None;
+        }
+        let int = groups.concat();
+        if int.is_empty() || !int.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        Some(Number {
+            negative: false,
+            int: int.parse().ok()?,
+            fraction: fraction.to_string(),
+            spoken: false,
+        })
+    }
+
+    fn english_integer(&self, start: usize) -> Option<(u64, usize)> {
+        if self.word(start) == Some("zero") {
+            return Some((0, start + 1));
+        }
+        let (mut total, mut group) = (0, 0);
+        let mut last: Option<Term> = None;
+        let mut last_scale = u64::MAX;
+        let mut end = start;
+        let mut i = start;
+        while i == start || self.follows(i) {
+            let Some(word) = self.word(i) else {
+                break;
+            };
+            // "one hundred and five"
+            if word == "and"
+                && matches!(last, Some(Term::Hundred | Term::Scale(_)))
+                && self.next(i + 1).and_then(english_term).is_some_and(|term| {
+                    matches!(term, Term::Unit(_) | Term::Teen(_) | Term::Tens(_))
+                })
+            {
+                i += 1;
+                continue;
+            }
+            let Some(term) = english_term(word) else {
+                break;
+            };
+            let after_group = matches!(last, None | Some(Term::Hundred | Term::Scale(_)));
+            match term {
+                Term::Unit(value) if after_group || matches!(last, Some(Term::Tens(_))) => {
+                    group += value
+                }
+                Term::Teen(value) | Term::Tens(value) if after_group => group += value,
+                Term::Hundred
+                    if matches!(last, Some(Term::Unit(_) | Term::Teen(_) | Term::Tens(_)))
+                        && group < 100 =>
+                {
+                    group *= 100
+                }
+                Term::Scale(scale) if group > 0 && scale < last_scale => {
+                    total += group * scale;
+                    group = 0;
+                    last_scale = scale;
+                }
+                _ => break,
+            }
+            last = Some(term);
+            i += 1;
+            end = i;
+        }
+        (end > start).then_some((total + group, end))
+    }
+
+    fn german_integer(&self, start: usize) -> Option<(u64, usize)> {
+        let mut group = german_compound(self.word(start)?)?;
+        let mut end = start + 1;
+        let mut total = 0;
+        let mut last_scale = u64::MAX;
+        while let Some(scale) = self.next(end).and_then(german_scale) {
+            if group == 0 || scale >= last_scale {
+                break;
+            }
+            total += group * scale;
+            last_scale = scale;
+            end += 1;
+            group = match self.next(end).and_then(german_compound) {
+                Some(value) if value < 1_000_000 => {
+                    end += 1;
+                    value
+                }
+                _ => 0,
+            };
+        }
+        Some((total + group, end))
+    }
+
+    fn integer(&self, start: usize) -> Option<(u64, usize)> {
+        match self.locale.language {
+            Language::English => self.english_integer(start),
+            Language::German => self.german_integer(start),
+        }
+    }
+
+    fn digit_word(&self, word: &str) -> Option<u64> {
+        match self.locale.language {
+            Language::English => english_digit(word),
+            Language::German => german_below_hundred(word).filter(|value| *value < 10),
+        }
+    }
+
+    /// Digits after "point" or "komma"
+    fn fraction(&self, start: usize) -> Option<(String, usize)> {
+        let mut fraction = String::new();
+        let mut i = start;
+        while let Some(digit) = self.next(i).and_then(|word| self.digit_word(word)) {
+            fraction.push_str(&digit.to_string());
+            i += 1;
+        }
+        if fraction.is_empty() && !self.english() {
+            // "drei komma fünfundzwanzig"
+            let value = self.next(start).and_then(german_compound)?;
+            return (value < 100).then(|| (value.to_string(), start + 1));
+        }
+        (!fraction.is_empty()).then_some((fraction, i))
+    }
+
+    fn cardinal(&self, i: usize) -> Option<(Number, usize)> {
+        let negative = matches!(self.word(i), Some("minus" | "negative")) && self.follows(i + 1);
+        let start = if negative { i + 1 } else { i };
+        if let Some(number) = self.digits(start) {
+            return Some((Number { negative, ..number }, start + 1));
+        }
+        let (int, mut end) = self.integer(start)?;
+        let mut fraction = String::new();
+        let point = if self.english() { "point" } else { "komma" };
+        if self.next(end) == Some(point) {
+            if let Some((digits, fraction_end)) = self.fraction(end + 1) {
+                fraction = digits;
+                end = fraction_end;
+            }
+        }
+        let number = Number {
+            negative,
+            int,
+            fraction,
+            spoken: true,
+        };
+        Some((number, end))
+    }
+
+    /// A whole number, said or written, within a range
+    fn whole(&self, i: usize, range: std::ops::RangeInclusive<u64>) -> Option<(u64, usize)> {
+        let (number, end) = self.cardinal(i)?;
+        number
+            .whole()
+            .filter(|value| range.contains(value))
+            .map(|value| (value, end))
+    }
+
+    fn ordinal(&self, i: usize) -> Option<(u64, usize)> {
+        let word = self.word(i)?;
+        if !self.english() {
+            return german_ordinal(word).map(|value| (value, i + 1));
+        }
+        // Already written as "21st"
+        if word.len() > 2 && word.is_char_boundary(word.len() - 2) {
+            let (digits, suffix) = word.split_at(word.len() - 2);
+            if let Ok(value) = digits.parse::<u64>() {
+                if suffix == english_suffix(value) {
+                    return Some((value, i + 1));
+                }
+            }
+        }
+        if let Some(term) = english_ordinal_term(word) {
+            let value = match term {
+                Term::Unit(value) | Term::Teen(value) | Term::Tens(value) => value,
+                Term::Hundred => 100,
+                Term::Scale(scale) => scale,
+            };
+            return Some((value, i + 1));
+        }
+        let (value, mut end) = self.english_integer(i)?;
+        // "one hundred and first"
+        if self.next(end) == Some("and")
+            && value % 100 == 0
+            && self.next(end + 1).and_then(english_ordinal_term).is_some()
+        {
+            end += 1;
+        }
+        let value = match self.next(end).and_then(english_ordinal_term)? {
+            Term::Unit(unit) if value % 10 == 0 && value % 100 != 10 => value + unit,
+            Term::Teen(teen) | Term::Tens(teen) if value % 100 == 0 => value + teen,
+            Term::Hundred if value < 100 => value * 100,
+            Term::Scale(scale) => value * scale,
+            _ => return None,
+        };
+        Some((value, end + 1))
+    }
+
+    /// Two digits said as a number from ten to ninety-nine
+    fn english_pair(&self, i: usize) -> Option<(u64, usize)> {
+        match english_term(self.word(i)?)? {
+            Term::Teen(value) => Some((value, i + 1)),
+            Term::Tens(tens) => match self.next(i + 1).and_then(english_term) {
+                Some(Term::Unit(unit)) => Some((tens + unit, i + 2)),
+                _ => Some((tens, i + 1)),
+            },
+            _ => None,
+        }
+    }
+
+    /// A year said in pairs, like "nineteen eighty four" or "twenty oh five"
+    fn english_year(
+        &self,
+        i: usize,
+        centuries: std::ops::RangeInclusive<u64>,
+    ) -> Option<(u64, usize)> {
+        let (century, end) = self
+            .english_pair(i)
+            .filter(|(value, _)| centuries.contains(value))?;
+        match self.next(end)? {
+            "hundred" => Some((century * 100, end + 1)),
+            "oh" => match self.next(end + 1).and_then(english_term)? {
+                Term::Unit(unit) => Some((century * 100 + unit, end + 2)),
+                _ => None,
+            },
+            _ => {
+                let (rest, end) = self.english_pair(end)?;
+                Some((century * 100 + rest, end))
+            }
+        }
+    }
+
+    fn year(&self, i: usize) -> Option<(u64, usize)> {
+        if self.english() {
+            if let Some(year) = self.english_year(i, 11..=20) {
+                return Some(year);
+            }
+        }
+        self.whole(i, 1000..=2999)
+    }
+
+    /// The month at `i` and how many days it can have
+    fn month(&self, i: usize) -> Option<(&'static str, u64)> {
+        let months = if self.english() {
+            ENGLISH_MONTHS
+        } else {
+            GERMAN_MONTHS
+        };
+        let word = &self.words.get(i)?;
+        // "may" is far more often a verb
+        if word.lower == "may" && word.core != "May" {
+            return None;
+        }
+        months
+            .iter()
+            .zip(MONTH_DAYS)
+            .find(|((name, _), _)| *name == word.lower)
+            .map(|((_, display), days)| (*display, days))
+    }
+
+    /// A year after a date, possibly after a comma on the day
+    fn date_year(&self, end: usize) -> Option<(u64, usize)> {
+        let comma = self.words.get(end.checked_sub(1)?)?.trail == ",";
+        if !(self.follows(end) || (comma && self.words.get(end)?.lead.is_empty())) {
+            return None;
+        }
+        self.year(end)
+    }
+
+    fn date(&self, i: usize) -> Option<(String, usize)> {
+        if !self.english() {
+            // "dritter März 2024" becomes "3. März 2024"
+            let (day, end) = self.ordinal(i)?;
+            let (month, days) = self.follows(end).then(|| self.month(end)).flatten()?;
+            if !(1..=days).contains(&day) {
+                return None;
+            }
+            let mut text = format!("{}. {}", day, month);
+            let mut end = end + 1;
+            if let Some((year, year_end)) = self.follows(end).then(|| self.year(end)).flatten() {
+                text.push_str(&format!(" {}", year));
+                end = year_end;
+            }
+            return Some((text, end));
+        }
+
+        let ((month, days), day, mut end) = if let Some(month) = self.month(i) {
+            // "March third", "March the third", "March 3"
+            let mut start = i + 1;
+            if self.next(start) == Some("the") {
+                start += 1;
+            }
+            if !self.follows(start) {
+                return None;
+            }
+            let (day, end) = match self.ordinal(start) {
+                Some(ordinal) => ordinal,
+                // A cardinal day only counts after a capitalized month, so
+                // "we march ten miles" stays as it is
+                None if self.words[i].core.starts_with(char::is_uppercase) => {
+                    self.whole(start, 1..=31)?
+                }
+                None => return None,
+            };
+            (month, day, end)
+        } else {
+            // "the third of March"
+            let start = if self.word(i) == Some("the") && self.follows(i + 1) {
+                i + 1
+            } else {
+                i
+            };
+            let (day, end) = self.ordinal(start)?;
+            if self.next(end) != Some("of") || !self.follows(end + 1) {
+                return None;
+            }
+            (self.month(end + 1)?, day, end + 2)
+        };
+        if !(1..=days).contains(&day) {
+            return None;
+        }
+
+        let mut text = format!("{} {}", month, day);
+        if let Some((year, year_end)) = self.date_year(end) {
+            text.push_str(&format!(", {}", year));
+            end = year_end;
+        }
+        Some((text, end))
+    }
+
+    fn meridiem(&self, i: usize) -> Option<&'static str> {
+        match self.next(i)?.replace('.', "").as_str() {
+            "am" => Some("AM"),
+            "pm" => Some("PM"),
+            _ => None,
+        }
+    }
+
+    fn time(&self, i: usize) -> Option<(String, usize)> {
+        if !self.english() {
+            // "drei Uhr dreißig" becomes "3:30 Uhr"
+            let (hour, end) = self.whole(i, 0..=24)?;
+            if self.next(end) != Some("uhr") {
+                return None;
+            }
+            return Some(
+                match self
+                    .follows(end + 1)
+                    .then(|| self.whole(end + 1, 1..=59))
+                    .flatten()
+                {
+                    Some((minutes, end)) => (format!("{}:{:02} Uhr", hour, minutes), end),
+                    None => (format!("{} Uhr", hour), end + 1),
+                },
+            );
+        }
+
+        // Already written as "3:30"
+        if let Some((hour, minutes)) = self.words[i].core.split_once(':') {
+            let (hour, minutes) = (hour.parse::<u64>().ok()?, minutes.parse::<u64>().ok()?);
+            let meridiem = self.meridiem(i + 1)?;
+            return Some((format!("{}:{:02} {}", hour, minutes, meridiem), i + 2));
+        }
+
+        let (hour, mut end) = self.whole(i, 1..=12)?;
+        let mut minutes = None;
+        let mut on_the_hour = false;
+        match self.next(end) {
+            Some("o'clock") => {
+                on_the_hour = true;
+                end += 1;
+            }
+            Some("oh") => {
+                if let Some(Term::Unit(unit)) = self.next(end + 1).and_then(english_term) {
+                    minutes = Some(unit);
+                    end += 2;
+                }
+            }
+            Some(_) => {
+                if let Some((value, minutes_end)) = self
+                    .english_integer(end)
+                    .filter(|(value, _)| (10..=59).contains(value))
+                {
+                    minutes = Some(value);
+                    end = minutes_end;
+                }
+            }
+            None => {}
+        }
+
+        let clock = match (minutes, on_the_hour) {
+            (Some(minutes), _) => format!("{}:{:02}", hour, minutes),
+            (None, true) => format!("{}:00", hour),
+            (None, false) => hour.to_string(),
+        };
+        match self.meridiem(end) {
+            Some(meridiem) => Some((format!("{} {}", clock, meridiem), end + 1)),
+            None if on_the_hour => Some((clock, end)),
+            None => None,
+        }
+    }
+
+    fn currency_symbol(&self, word: &str) -> Option<&'static str> {
+        match (self.locale.language, word) {
+            (Language::English, "dollar" | "dollars") => Some("$"),
+            (Language::English, "euro" | "euros") => Some("€"),
+            (Language::English, "pound" | "pounds") if self.locale.british => Some("£"),
+            (Language::English, "yen") => Some("¥"),
+            (Language::German, "euro" | "euros") => Some("€"),
+            (Language::German, "dollar") => Some("$"),
+            (Language::German, "pfund") => Some("£"),
+            (Language::German, "yen") => Some("¥"),
+            _ => None,
+        }
+    }
+
+    fn is_minor_unit(&self, word: &str) -> bool {
+        matches!(word, "cent" | "cents")
+            || self.english() && self.locale.british && matches!(word, "penny" | "pence" | "p")
+    }
+
+    fn currency(&self, i: usize) -> Option<(String, usize)> {
+        let (mut amount, end) = self.cardinal(i)?;
+        let symbol = self.next(end).and_then(|word| self.currency_symbol(word))?;
+        let mut end = end + 1;
+
+        // "twenty five dollars and fifty cents"
+        let mut cents_start = end;
+        if matches!(self.next(end), Some("and" | "und")) {
+            cents_start += 1;
+        }
+        if amount.fraction.is_empty() && self.follows(cents_start) {
+            if let Some((cents, cents_end)) = self.whole(cents_start, 0..=99) {
+                if self
+                    .next(cents_end)
+                    .is_some_and(|word| self.is_minor_unit(word))
+                {
+                    amount.fraction = format!("{:02}", cents);
+                    end = cents_end + 1;
+                }
+            }
+        }
+
+        let number = self.format_number(&Number {
+            negative: false,
+            ..amount.clone()
+        });
+        let sign = if amount.negative { "-" } else { "" };
+        let text = match self.locale.language {
+            Language::English => format!("{}{}{}", sign, symbol, number),
+            Language::German => format!("{}{} {}", sign, number, symbol),
+        };
+        Some((text, end))
+    }
+
+    fn percent(&self, i: usize) -> Option<(String, usize)> {
+        let (number, end) = self.cardinal(i)?;
+        let end = match (self.next(end), self.next(end + 1)) {
+            (Some("percent" | "prozent"), _) => end + 1,
+            (Some("per"), Some("cent")) if self.english() => end + 2,
+            _ => return None,
+        };
+        let text = match self.locale.language {
+            Language::English => format!("{}%", self.format_number(&number)),
+            Language::German => format!("{} %", self.format_number(&number)),
+        };
+        Some((text, end))
+    }
+
+    fn unit(&self, i: usize) -> Option<(String, usize)> {
+        let (number, end) = self.cardinal(i)?;
+        let units = match self.locale.language {
+            Language::English => ENGLISH_UNITS,
+            Language::German => GERMAN_UNITS,
+        };
+        let (words, symbol) = units.iter().find(|(words, _)| {
+            words.iter().enumerate().all(|(offset, accepted)| {
+                self.next(end + offset)
+                    .is_some_and(|word| accepted.contains(&word))
+            })
+        })?;
+        let number = self.format_number(&number);
+        let attached = symbol.starts_with('°') && (self.english() || *symbol == "°");
+        let text = if attached {
+            format!("{}{}", number, symbol)
+        } else {
+            format!("{} {}", number, symbol)
+        };
+        Some((text, end + words.len()))
+    }
+
+    /// A number on its own; small whole numbers stay words
+    fn plain(&self, i: usize) -> Option<(String, usize)> {
+        let set_phrase = ORDINAL_PHRASES.iter().any(|phrase| {
+            phrase.iter().enumerate().all(|(offset, word)| {
+                (offset == 0 || self.follows(i + offset)) && self.word(i + offset) == Some(*word)
+            })
+        });
+        if set_phrase {
+            return None;
+        }
+        if let Some((value, end)) = self.ordinal(i) {
+            // Small ordinals stay words and "21st" is already written
+            if value < 10
+                || self.words[i]
+                    .lower
+                    .starts_with(|c: char| c.is_ascii_digit())
+            {
+                return None;
+            }
+            return Some(match self.locale.language {
+                Language::English => (format!("{}{}", value, english_suffix(value)), end),
+                Language::German => (format!("{}.", value), end),
+            });
+        }
+        if self.english() {
+            if let Some((year, end)) = self.english_year(i, 19..=20) {
+                return Some((year.to_string(), end));
+            }
+        }
+        let (number, end) = self.cardinal(i)?;
+        let small = number.whole().is_some_and(|value| value < 10);
+        if !number.spoken || small {
+            return None;
+        }
+        Some((self.format_number(&number), end))
+    }
+
+    fn rewrite(&self, i: usize) -> Option<(String, usize)> {
+        self.date(i)
+            .or_else(|| self.time(i))
+            .or_else(|| self.currency(i))
+            .or_else(|| self.percent(i))
+            .or_else(|| self.unit(i))
+            .or_else(|| self.plain(i))
+    }
+}
+
+/// A spoken number at the start of `words`, like "three thousand four hundred", the
+/// year "twenty twenty four" or "fünfundzwanzig", and how many words it takes up.
+/// Locales without inverse text normalization read English number words.
+pub fn spoken_number(words: &[&str], locale: &str) -> Option<(u64, usize)> {
+    let locale = Locale::parse(locale).unwrap_or(Locale {
+        language: Language::English,
+        british: false,
+    });
+    let words: Vec<Word> = words
+        .iter()
+        .map(|word| Word::new("", word, "", " "))
+        .collect();
+    let normalizer = Normalizer {
+        locale,
+        words: &words,
+    };
+    let integer = normalizer.integer(0);
+    if !normalizer.english() {
+        return integer;
+    }
+    match normalizer.english_year(0, 10..=99) {
+        Some(year) if integer.is_none_or(|(_, end)| year.1 > end) => Some(year),
+        _ => integer,
+    }
+}
+
+/// Write spoken numbers, money, percentages, times, dates and units the way they are
+/// typed in the given locale. Text in unsupported languages is returned unchanged.
+pub fn normalize(text: &str, locale: &str) -> String {
+    let Some(locale) = Locale::parse(locale) else {
+        debug!("No inverse text normalization for locale {}", locale);
+        return text.to_string();
+    };
+    let (mut normalized, words) = split_words(text, locale.language);
+    let normalizer = Normalizer {
+        locale,
+        words: &words,
+    };
+
+    let mut i = 0;
+    while i < words.len() {
+        match normalizer.rewrite(i) {
+            Some((replacement, end)) => {
+                let last = &words[end - 1];
+                normalized.push_str(&words[i].lead);
+                normalized.push_str(&replacement);
+                normalized.push_str(&last.trail);
+                normalized.push_str(&last.sep);
+                i = end;
+            }
+            None => {
+                normalized.push_str(&words[i].original());
+                normalized.push_str(&words[i].sep);
+                i += 1;
+            }
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(locale: &str, cases: &[(&str, &str)]) {
+        for (spoken, written) in cases {
+            assert_eq!(&normalize(spoken, locale), written, "{:?}", spoken);
+        }
+    }
+
+    #[test]
+    fn english_cardinals() {
+        check(
+            "en-US",
+            &[
+                ("I have two cats", "I have two cats"),
+                ("twenty five people came", "25 people came"),
+                ("twenty-five people", "25 people"),
+                ("one hundred", "100"),
+                ("one hundred and five", "105"),
+                ("three hundred forty two", "342"),
+                ("twelve hundred", "1200"),
+                ("two thousand twenty four", "2024"),
+                ("one thousand five hundred", "1500"),
+                ("fifteen thousand", "15,000"),
+                ("two million three hundred thousand", "2,300,000"),
+                ("ten", "10"),
+                ("zero", "zero"),
+                ("zero point five", "0.5"),
+                ("three point one four", "3.14"),
+                ("minus twelve", "-12"),
+                ("one, two, three", "one, two, three"),
+                ("twenty, thirty", "20, 30"),
+                ("nineteen eighty four", "1984"),
+                ("in twenty twenty", "in 2020"),
+                ("the 25 results", "the 25 results"),
+                ("a good point", "a good point"),
+            ],
+        );
+    }
+
+    #[test]
+    fn english_ordinals() {
+        check(
+            "en-US",
+            &[
+                ("the first time", "the first time"),
+                ("second place", "second place"),
+                ("the tenth floor", "the 10th floor"),
+                ("the twenty first century", "the 21st century"),
+                ("twenty-second", "22nd"),
+                ("the thirteenth", "the 13th"),
+                ("one hundred and first", "101st"),
+                ("two thousand and third", "2003rd"),
+                ("one hundred and one", "101"),
+                ("one hundred first", "101st"),
+                ("the fortieth anniversary", "the 40th anniversary"),
+                ("the hundredth visitor", "the 100th visitor"),
+                ("twelfth night", "twelfth night"),
+                ("at the Eleventh Hour", "at the Eleventh Hour"),
+                ("the twelfth floor", "the 12th floor"),
+            ],
+        );
+    }
+
+    #[test]
+    fn english_currency() {
+        check(
+            "en-US",
+            &[
+                ("twenty five dollars", "$25"),
+                ("one dollar", "$1"),
+                ("twenty five dollars and fifty cents", "$25.50"),
+                ("five dollars five cents", "$5.05"),
+                ("25 dollars", "$25"),
+                ("3.50 dollars", "$3.50"),
+                ("one million dollars", "$1,000,000"),
+                ("a million dollars", "a million dollars"),
+                ("ten thousand euros", "€10,000"),
+                ("thirty yen", "¥30"),
+                ("minus five dollars", "-$5"),
+                ("twenty five cents", "25 cents"),
+            ],
+        );
+        check(
+            "en-GB",
+            &[
+                ("ten pounds", "£10"),
+                ("ten pounds and fifty pence", "£10.50"),
+            ],
+        );
+        check("en-US", &[("ten pounds", "10 lb")]);
+    }
+
+    #[test]
+    fn english_percentages() {
+        check(
+            "en-US",
+            &[
+                ("fifty percent", "50%"),
+                ("five percent", "5%"),
+                ("twelve point five percent", "12.5%"),
+                ("ten per cent", "10%"),
+                ("100 percent", "100%"),
+            ],
+        );
+    }
+
+    #[test]
+    fn english_times() {
+        check(
+            "en-US",
+            &[
+                ("at three thirty pm", "at 3:30 PM"),
+                ("at three pm", "at 3 PM"),
+                ("seven oh five a.m.", "7:05 AM"),
+                ("at five o'clock", "at 5:00"),
+                ("at five o’clock pm", "at 5:00 PM"),
+                ("twelve fifteen p.m.", "12:15 PM"),
+                ("eleven forty five am", "11:45 AM"),
+                ("3:30 pm", "3:30 PM"),
+                ("at 9 am", "at 9 AM"),
+                ("thirteen pm", "13 pm"),
+            ],
+        );
+    }
+
+    #[test]
+    fn english_dates() {
+        check(
+            "en-US",
+            &[
+                ("march third", "March 3"),
+                ("on March the third", "on March 3"),
+                ("on the third of march", "on March 3"),
+                ("March 3rd", "March 3"),
+                ("July fourth, nineteen seventy six", "July 4, 1976"),
+                (
+                    "december twenty fifth two thousand twenty four",
+                    "December 25, 2024",
+                ),
+                ("January 15 2025", "January 15, 2025"),
+                ("May first", "May 1"),
+                ("you may first check", "you may first check"),
+                ("we march ten miles", "we march 10 mi"),
+                ("June thirty first", "June 31st"),
+                ("April thirty first", "April 31st"),
+                ("on the thirty first of april", "on the 31st of april"),
+                ("May thirty first", "May 31"),
+                ("February twenty ninth", "February 29"),
+                ("on the first", "on the first"),
+            ],
+        );
+    }
+
+    #[test]
+    fn english_units() {
+        check(
+            "en-US",
+            &[
+                ("five kilometers", "5 km"),
+                ("one meter", "1 m"),
+                ("twenty degrees", "20°"),
+                ("minus five degrees celsius", "-5°C"),
+                ("seventy two degrees fahrenheit", "72°F"),
+                ("sixty miles per hour", "60 mph"),
+                ("two point five kilograms", "2.5 kg"),
+                ("sixteen gigabytes", "16 GB"),
+                ("six feet", "6 ft"),
+                ("twelve inches", "12 in"),
+                ("half a liter", "half a liter"),
+            ],
+        );
+    }
+
+    #[test]
+    fn keeps_punctuation_case_and_spacing() {
+        check(
+            "en-US",
+            &[
+                (
+                    "twenty five dollars on march third at three thirty pm",
+                    "$25 on March 3 at 3:30 PM",
+                ),
+                ("It costs twenty dollars.", "It costs $20."),
+                ("(fifty percent)", "(50%)"),
+                (
+                    "Meet at three pm.\nBring twelve chairs",
+                    "Meet at 3 PM.\nBring 12 chairs",
+                ),
+                ("  twenty  people ", "  20  people "),
+                ("twenty\nfive", "20\nfive"),
+                ("Twenty Five people", "25 people"),
+            ],
+        );
+    }
+
+    #[test]
+    fn german() {
+        check(
+            "de-DE",
+            &[
+                ("zwei Katzen", "zwei Katzen"),
+                ("fünfundzwanzig Leute", "25 Leute"),
+                ("dreihundertzwölf", "312"),
+                ("zweitausendvierundzwanzig", "2024"),
+                ("eine Million", "1.000.000"),
+                ("zwei Millionen dreihunderttausend", "2.300.000"),
+                ("fünfzehntausend", "15.000"),
+                ("drei komma fünf", "3,5"),
+                ("minus zwölf", "-12"),
+                ("das zwanzigste Jahrhundert", "das 20. Jahrhundert"),
+                ("der dritte Versuch", "der dritte Versuch"),
+                ("fünfundzwanzig Euro", "25 €"),
+                ("zehn Euro und fünfzig Cent", "10,50 €"),
+                ("3,50 Euro", "3,50 €"),
+                ("zehn Prozent", "10 %"),
+                ("um drei Uhr dreißig", "um 3:30 Uhr"),
+                ("um fünfzehn Uhr", "um 15 Uhr"),
+                ("am dritten März", "am 3. März"),
+                ("dritter märz zweitausendvierundzwanzig", "3. März 2024"),
+                ("fünf Kilometer", "5 km"),
+                ("zwanzig Grad Celsius", "20 °C"),
+                ("neunzig Grad", "90°"),
+                ("achtzig Kilometer pro Stunde", "80 km/h"),
+                ("die Liste", "die Liste"),
+            ],
+        );
+    }
+
+    #[test]
+    fn reads_numbers_from_words() {
+        let cases = [
+            ("en", "twenty five people", Some((25, 2))),
+            ("en", "one hundred and twelve", Some((112, 4))),
+            ("en", "twenty twenty four", Some((2024, 3))),
+            ("en", "nineteen oh five", Some((1905, 3))),
+            ("en", "five five", Some((5, 1))),
+            ("en", "point", None),
+            ("fr-FR", "twenty five", Some((25, 2))),
+            ("de-DE", "Fünfundzwanzig Leute", Some((25, 1))),
+            (
+                "de-DE",
+                "zwei Millionen dreihunderttausend",
+                Some((2_300_000, 3)),
+            ),
+            ("de-DE", "twenty", None),
+        ];
+        for (locale, spoken, expected) in cases {
+            let words: Vec<&str> = spoken.split_whitespace().collect();
+            assert_eq!(spoken_number(&words, locale), expected, "{:?}", spoken);
+        }
+    }
+
+    #[test]
+    fn other_languages_are_unchanged() {
+        assert_eq!(normalize("vingt-cinq euros", "fr-FR"), "vingt-cinq euros");
+        assert!(supports("en"));
+        assert!(supports("de_AT"));
+        assert!(!supports("fr-FR"));
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+use tracing::debug;
+
+/// Options for a prompt step against an OpenAI-compatible `/chat/completions` endpoint - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LlmStepConfig {
+    /// e.g. `https://api.openai.com/v1`, `https://api.groq.com/openai/v1`,
+    /// or a local server such as `http://localhost:11434/v1`
+    pub base_url: String,
+    /// Bearer token; optional for self-hosted servers
+    pub api_key: Option<String>,
+    pub model: String,
+    /// System prompt; supports the same placeholders as the user prompt
+    #[serde(default)]
+    pub system_prompt: String,
+    /// User prompt; `{{input}}` is replaced with the text reaching this step
+    #[serde(default = "default_user_prompt")]
+    pub user_prompt: String,
+    pub temperature: Option<f32>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_user_prompt() -> String {
+    "{{input}}".to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+/// Send the rendered prompts and return the model's reply
+pub async fn complete(
+    config: &LlmStepConfig,
+    client: &reqwest::Client,
+    system_prompt: &str,
+    user_prompt: &str,
+) -> Result<String, String> {
+    let url = format!("{}/chat/completions", config.base_url.trim_end_matches('/'));
+    let mut messages = Vec::new();
+    if !system_prompt.is_empty() {
+        messages.push(json!({"role": "system", "content": system_prompt}));
+    }
+    messages.push(json!({"role": "user", "content": user_prompt}));
+
+    let mut body = json!({"model": config.model, "messages": messages});
+    if let Some(temperature) = config.temperature {
+        body["temperature"] = json!(temperature);
+    }
+
+    let mut request = client
+        .post(&url)
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .json(&body);
+    if let Some(api_key) = &config.api_key {
+        request = request.bearer_auth(api_key);
+    }
+
+    debug!("Sending pipeline prompt to {} ({})", url, config.model);
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("LLM request failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("LLM error {}: {}", status, body));
+    }
+
+    let reply: ChatResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid LLM response: {}", e))?;
+    reply
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|choice| choice.message.content)
+        .map(|content| content.trim().to_string())
+        .ok_or_else(|| "LLM response had no content".to_string())
+}
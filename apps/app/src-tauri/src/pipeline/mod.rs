@@ -0,0 +1,731 @@
+pub mod itn;
+pub mod llm;
+
+use crate::backend::http::app_client;
+use crate::backend::VoiceProcessResponse;
+use crate::context::VoiceContext;
+use crate::context_policy;
+use crate::dictionary::app_dictionary;
+use crate::field_format::{format_for_field, FieldKind};
+use crate::smart_spacing;
+use crate::store::{JsonStore, Validate};
+use crate::voice_commands;
+use llm::LlmStepConfig;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{debug, info, warn};
+
+const PIPELINE_SETTINGS_FILE: &str = "pipeline_settings.json";
+const DEFAULT_PIPELINE_NAME: &str = "default";
+
+/// How a case step rewrites text - matches TypeScript interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CaseMode {
+    Lower,
+    Upper,
+    /// Capitalize the first letter of each sentence, leaving the rest alone
+    Sentence,
+    /// Capitalize the first letter of each word
+    Title,
+}
+
+/// One transformation applied to the text - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum StepKind {
+    /// Replace every occurrence of literal text
+    FindReplace {
+        find: String,
+        replace: String,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    /// Regex substitution; the replacement may refer to groups as `$1` or `${name}`
+    Regex {
+        pattern: String,
+        replacement: String,
+    },
+    Case {
+        mode: CaseMode,
+    },
+    /// Trim surrounding whitespace, optionally collapsing runs of spaces
+    Trim {
+        #[serde(default)]
+        collapse_whitespace: bool,
+    },
+    /// Wrap the text; both templates support `{{app}}` and `{{locale}}`
+    Template {
+        #[serde(default)]
+        prefix: String,
+        #[serde(default)]
+        suffix: String,
+    },
+    /// Rewrite the text with a language model
+    Llm(LlmStepConfig),
+    /// Write spoken numbers, money, percentages, times, dates and units as digits and
+    /// symbols, in the step's locale or else the dictation locale
+    Normalize {
+        #[serde(default)]
+        locale: Option<String>,
+    },
+}
+
+impl StepKind {
+    /// Short name used in logs
+    pub fn label(&self) -> &'static str {
+        match self {
+            StepKind::FindReplace { .. } => "find/replace",
+            StepKind::Regex { .. } => "regex",
+            StepKind::Case { .. } => "case",
+            StepKind::Trim { .. } => "trim",
+            StepKind::Template { .. } => "template",
+            StepKind::Llm(_) => "llm",
+            StepKind::Normalize { .. } => "normalize",
+        }
+    }
+}
+
+/// A step that can be switched off without removing it - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStep {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub kind: StepKind,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A named, ordered list of steps - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pipeline {
+    pub name: String,
+    #[serde(default)]
+    pub steps: Vec<PipelineStep>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PIPELINE_NAME.to_string(),
+            steps: Vec::new(),
+        }
+    }
+}
+
+/// All pipelines and which one runs on dictated text - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineSettings {
+    pub active_pipeline: String,
+    pub pipelines: Vec<Pipeline>,
+}
+
+impl Default for PipelineSettings {
+    fn default() -> Self {
+        Self {
+            active_pipeline: DEFAULT_PIPELINE_NAME.to_string(),
+            pipelines: vec![Pipeline::default()],
+        }
+    }
+}
+
+impl PipelineSettings {
+    pub fn pipeline(&self, name: &str) -> Option<&Pipeline> {
+        self.pipelines.iter().find(|p| p.name == name)
+    }
+}
+
+impl Validate for PipelineSettings {
+    fn validate(&self) -> Result<(), String> {
+        for pipeline in &self.pipelines {
+            for step in &pipeline.steps {
+                if let StepKind::Regex { pattern, .. } = &step.kind {
+                    Regex::new(pattern).map_err(|e| {
+                        format!("Invalid regex in pipeline '{}': {}", pipeline.name, e)
+                    })?;
+                }
+                if let StepKind::Normalize {
+                    locale: Some(locale),
+                } = &step.kind
+                {
+                    if !itn::supports(locale) {
+                        return Err(format!(
+                            "Number normalization in pipeline '{}' doesn't support locale {}",
+                            pipeline.name, locale
+                        ));
+                    }
+                }
+            }
+        }
+        if !self
+            .pipelines
+            .iter()
+            .any(|p| p.name == self.active_pipeline)
+        {
+            return Err(format!("Unknown pipeline '{}'", self.active_pipeline));
+        }
+        Ok(())
+    }
+}
+
+/// What one step did, for debugging transformations - matches TypeScript interface
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepLog {
+    pub index: usize,
+    pub step: &'static str,
+    pub input: String,
+    pub output: String,
+    pub duration_ms: u64,
+    /// Set when the step failed; its input is then passed on unchanged
+    pub error: Option<String>,
+}
+
+/// Result of running a pipeline - matches TypeScript interface
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineRun {
+    pub pipeline: String,
+    pub input: String,
+    pub output: String,
+    pub steps: Vec<StepLog>,
+}
+
+/// Fill `{{input}}`, `{{app}}` and `{{locale}}` placeholders
+fn render(template: &str, input: &str, context: &VoiceContext) -> String {
+    template
+        .replace("{{app}}", &context.package_name)
+        .replace("{{locale}}", &context.locale)
+        .replace("{{input}}", input)
+}
+
+fn capitalize_first(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn change_case(text: &str, mode: CaseMode) -> String {
+    match mode {
+        CaseMode::Lower => text.to_lowercase(),
+        CaseMode::Upper => text.to_uppercase(),
+        CaseMode::Title => text
+            .split(' ')
+            .map(capitalize_first)
+            .collect::<Vec<_>>()
+            .join(" "),
+        CaseMode::Sentence => {
+            let mut result = String::with_capacity(text.len());
+            let mut sentence_start = true;
+            for c in text.chars() {
+                if sentence_start && c.is_alphanumeric() {
+                    result.extend(c.to_uppercase());
+                    sentence_start = false;
+                } else {
+                    result.push(c);
+                }
+                if matches!(c, '.' | '!' | '?' | '\n') {
+                    sentence_start = true;
+                }
+            }
+            result
+        }
+    }
+}
+
+/// Apply one step to the text
+async fn run_step(
+    kind: &StepKind,
+    input: &str,
+    context: &VoiceContext,
+    client: &reqwest::Client,
+) -> Result<String, String> {
+    match kind {
+        StepKind::FindReplace { find, .. } if find.is_empty() => Ok(input.to_string()),
+        StepKind::FindReplace {
+            find,
+            replace,
+            ignore_case: false,
+        } => Ok(input.replace(find.as_str(), replace)),
+        StepKind::FindReplace { find, replace, .. } => {
+            let pattern = RegexBuilder::new(&regex::escape(find))
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| e.to_string())?;
+            Ok(pattern
+                .replace_all(input, regex::NoExpand(replace))
+                .into_owned())
+        }
+        StepKind::Regex {
+            pattern,
+            replacement,
+        } => {
+            let pattern =
+                Regex::new(pattern).map_err(|e| format!("Invalid regex pattern: {}", e))?;
+            Ok(pattern
+                .replace_all(input, replacement.as_str())
+                .into_owned())
+        }
+        StepKind::Case { mode } => Ok(change_case(input, *mode)),
+        StepKind::Trim {
+            collapse_whitespace,
+        } => Ok(if *collapse_whitespace {
+            input.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else {
+            input.trim().to_string()
+        }),
+        StepKind::Template { prefix, suffix } => Ok(format!(
+            "{}{}{}",
+            render(prefix, input, context),
+            input,
+            render(suffix, input, context)
+        )),
+        StepKind::Llm(config) => {
+            let system_prompt = render(&config.system_prompt, input, context);
+            let user_prompt = render(&config.user_prompt, input, context);
+            llm::complete(config, client, &system_prompt, &user_prompt).await
+        }
+        StepKind::Normalize { locale } => Ok(itn::normalize(
+            input,
+            locale.as_deref().unwrap_or(&context.locale),
+        )),
+    }
+}
+
+/// Run every enabled step in order. A failing step is logged and skipped so
+/// a broken transformation never loses the dictated text.
+pub async fn run_pipeline(
+    pipeline: &Pipeline,
+    text: &str,
+    context: &VoiceContext,
+    client: &reqwest::Client,
+) -> PipelineRun {
+    let mut current = text.to_string();
+    let mut steps = Vec::new();
+
+    for (index, step) in pipeline.steps.iter().enumerate() {
+        if !step.enabled {
+            continue;
+        }
+        let started = Instant::now();
+        let result = run_step(&step.kind, &current, context, client).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let log = match result {
+            Ok(output) => {
+                debug!(
+                    "Pipeline '{}' step {} ({}): {:?} -> {:?}",
+                    pipeline.name,
+                    index,
+                    step.kind.label(),
+                    current,
+                    output
+                );
+                StepLog {
+                    index,
+                    step: step.kind.label(),
+                    input: std::mem::replace(&mut current, output.clone()),
+                    output,
+                    duration_ms,
+                    error: None,
+                }
+            }
+            Err(error) => {
+                warn!(
+                    "Pipeline '{}' step {} ({}) failed, skipping: {}",
+                    pipeline.name,
+                    index,
+                    step.kind.label(),
+                    error
+                );
+                StepLog {
+                    index,
+                    step: step.kind.label(),
+                    input: current.clone(),
+                    output: current.clone(),
+                    duration_ms,
+                    error: Some(error),
+                }
+            }
+        };
+        steps.push(log);
+    }
+
+    PipelineRun {
+        pipeline: pipeline.name.clone(),
+        input: text.to_string(),
+        output: current,
+        steps,
+    }
+}
+
+/// Pipeline settings persisted as JSON in the app data directory
+#[derive(Debug, Default)]
+pub struct PipelineStore {
+    store: JsonStore<PipelineSettings>,
+}
+
+impl PipelineStore {
+    /// Load saved pipelines from the given app data directory, falling back to defaults
+    pub fn load(app_dir: PathBuf) -> Self {
+        let store = JsonStore::<PipelineSettings>::load(app_dir.join(PIPELINE_SETTINGS_FILE));
+        info!(
+            "Loaded {} text pipelines, active: {}",
+            store.get().pipelines.len(),
+            store.get().active_pipeline
+        );
+        Self { store }
+    }
+
+    pub fn settings(&self) -> &PipelineSettings {
+        self.store.get()
+    }
+
+    /// Replace all pipelines and write them to disk
+    pub fn set_settings(&mut self, settings: PipelineSettings) -> Result<(), String> {
+        self.store.set(settings)
+    }
+
+    /// The pipeline that runs on dictated text
+    pub fn active_pipeline(&self) -> Pipeline {
+        let settings = self.settings();
+        settings
+            .pipeline(&settings.active_pipeline)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The named pipeline, or the active one when the name is unknown
+    pub fn pipeline_or_active(&self, name: &str) -> Pipeline {
+        match self.settings().pipeline(name) {
+            Some(pipeline) => pipeline.clone(),
+            None => {
+                warn!("Unknown pipeline '{}', using the active one", name);
+                self.active_pipeline()
+            }
+        }
+    }
+}
+
+/// Apply spoken commands, fix dictionary terms, run a pipeline, apply the field's output
+/// rules and fit the result to the cursor on a backend response's final text before it
+/// is inserted. `pipeline_name` overrides the active pipeline.
+///
+/// `context` is the unfiltered context: the cursor text is only used locally, and the
+/// context policy is applied to the copy a pipeline may send elsewhere.
+pub async fn apply_to_response(
+    app: &AppHandle,
+    response: &mut VoiceProcessResponse,
+    context: &VoiceContext,
+    pipeline_name: Option<&str>,
+) {
+    let Some(text) = response.final_text.as_deref() else {
+        return;
+    };
+    let language = response
+        .transcription
+        .as_ref()
+        .and_then(|transcription| transcription.language.as_deref())
+        .unwrap_or(&context.locale);
+    let commanded = voice_commands::apply_for_app(app, text, language);
+    if commanded != text {
+        debug!("Voice commands applied {:?} -> {:?}", text, commanded);
+    }
+    let corrected = app_dictionary(app).apply(&commanded);
+    if corrected != commanded {
+        debug!("Dictionary corrected {:?} -> {:?}", commanded, corrected);
+    }
+
+    let pipeline = match app.try_state::<Mutex<PipelineStore>>() {
+        Some(store) => match store.lock() {
+            Ok(store) => match pipeline_name {
+                Some(name) => store.pipeline_or_active(name),
+                None => store.active_pipeline(),
+            },
+            Err(e) => {
+                warn!("Failed to lock pipeline store: {}", e);
+                Pipeline::default()
+            }
+        },
+        None => Pipeline::default(),
+    };
+    let processed = if pipeline.steps.is_empty() {
+        corrected
+    } else {
+        let mut shared = context.clone();
+        context_policy::apply_for_app(app, &mut shared);
+        let run = run_pipeline(&pipeline, &corrected, &shared, &app_client(app)).await;
+        if let Err(e) = app.emit("text-pipeline-completed", &run) {
+            warn!("Failed to emit pipeline run: {}", e);
+        }
+        run.output
+    };
+
+    let kind = FieldKind::of(context);
+    let formatted = format_for_field(&processed, kind, &context.locale);
+    if formatted != processed {
+        debug!(
+            "Formatted text for {:?} field {:?} -> {:?}",
+            kind, processed, formatted
+        );
+    }
+    let fitted = if kind.is_prose() {
+        smart_spacing::fit_to_context(&formatted, context)
+    } else {
+        formatted.clone()
+    };
+    if fitted != formatted {
+        debug!("Fitted text to cursor {:?} -> {:?}", formatted, fitted);
+    }
+    response.final_text = Some(fitted);
+}
+
+/// Get all text pipelines
+#[tauri::command]
+pub async fn get_pipeline_settings(
+    store: State<'_, Mutex<PipelineStore>>,
+) -> Result<PipelineSettings, String> {
+    let store = store.lock().map_err(|e| e.to_string())?;
+    Ok(store.settings().clone())
+}
+
+/// Replace and save all text pipelines
+#[tauri::command]
+pub async fn set_pipeline_settings(
+    settings: PipelineSettings,
+    store: State<'_, Mutex<PipelineStore>>,
+) -> Result<(), String> {
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    store.set_settings(settings)
+}
+
+/// Run a pipeline on sample text and return each step's input and output.
+/// Uses the active pipeline when `name` is not given.
+#[tauri::command]
+pub async fn run_text_pipeline(
+    app: AppHandle,
+    text: String,
+    name: Option<String>,
+    context: Option<VoiceContext>,
+    store: State<'_, Mutex<PipelineStore>>,
+) -> Result<PipelineRun, String> {
+    let pipeline = {
+        let store = store.lock().map_err(|e| e.to_string())?;
+        match name {
+            Some(name) => store
+                .settings()
+                .pipeline(&name)
+                .cloned()
+                .ok_or_else(|| format!("Unknown pipeline '{}'", name))?,
+            None => store.active_pipeline(),
+        }
+    };
+    let context = context.unwrap_or_default();
+    Ok(run_pipeline(&pipeline, &text, &context, &app_client(&app)).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{MockBackend, MockResponse};
+
+    fn step(kind: StepKind) -> PipelineStep {
+        PipelineStep {
+            enabled: true,
+            kind,
+        }
+    }
+
+    async fn run(steps: Vec<PipelineStep>, text: &str) -> PipelineRun {
+        let pipeline = Pipeline {
+            name: "test".to_string(),
+            steps,
+        };
+        let context = VoiceContext {
+            package_name: "com.apple.mail".to_string(),
+            ..VoiceContext::default()
+        };
+        run_pipeline(&pipeline, text, &context, &reqwest::Client::new()).await
+    }
+
+    #[tokio::test]
+    async fn runs_steps_in_order_and_logs_each() {
+        let run = run(
+            vec![
+                step(StepKind::Trim {
+                    collapse_whitespace: true,
+                }),
+                step(StepKind::FindReplace {
+                    find: "GPT".to_string(),
+                    replace: "ChatGPT".to_string(),
+                    ignore_case: true,
+                }),
+                step(StepKind::Regex {
+                    pattern: r"(\d+) percent".to_string(),
+                    replacement: "$1%".to_string(),
+                }),
+                step(StepKind::Case {
+                    mode: CaseMode::Sentence,
+                }),
+                step(StepKind::Template {
+                    prefix: String::new(),
+                    suffix: " (sent from {{app}})".to_string(),
+                }),
+            ],
+            "  ask gpt about   50 percent. thanks ",
+        )
+        .await;
+
+        assert_eq!(
+            run.output,
+            "Ask ChatGPT about 50%. Thanks (sent from com.apple.mail)"
+        );
+        assert_eq!(run.steps.len(), 5);
+        assert_eq!(run.steps[0].output, "ask gpt about 50 percent. thanks");
+        assert_eq!(run.steps[1].input, run.steps[0].output);
+        assert!(run.steps.iter().all(|log| log.error.is_none()));
+    }
+
+    #[tokio::test]
+    async fn skips_disabled_and_failing_steps() {
+        let mut disabled = step(StepKind::Case {
+            mode: CaseMode::Upper,
+        });
+        disabled.enabled = false;
+        let run = run(
+            vec![
+                disabled,
+                step(StepKind::Regex {
+                    pattern: "(unclosed".to_string(),
+                    replacement: String::new(),
+                }),
+            ],
+            "keep me",
+        )
+        .await;
+
+        assert_eq!(run.output, "keep me");
+        assert_eq!(run.steps.len(), 1);
+        assert!(run.steps[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("Invalid regex"));
+    }
+
+    #[test]
+    fn changes_case() {
+        assert_eq!(change_case("hello world", CaseMode::Title), "Hello World");
+        assert_eq!(change_case("Hello World", CaseMode::Lower), "hello world");
+        assert_eq!(
+            change_case("one. two? three\nfour", CaseMode::Sentence),
+            "One. Two? Three\nFour"
+        );
+    }
+
+    #[tokio::test]
+    async fn llm_step_uses_rendered_prompt() {
+        let backend = MockBackend::start([MockResponse::Json(
+            r#"{"choices": [{"message": {"role": "assistant", "content": " Dear team, \n"}}]}"#,
+        )]);
+        let run = run(
+            vec![step(StepKind::Llm(LlmStepConfig {
+                base_url: backend.profile().base_url,
+                api_key: Some("key".to_string()),
+                model: "test-model".to_string(),
+                system_prompt: "Rewrite for {{app}}".to_string(),
+                user_prompt: "{{input}}".to_string(),
+                temperature: None,
+                timeout_secs: 5,
+            }))],
+            "hey team",
+        )
+        .await;
+
+        assert_eq!(run.output, "Dear team,");
+        let request = &backend.requests()[0];
+        assert_eq!(request.path, "/api/voice/chat/completions");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer key"));
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["messages"][0]["content"], "Rewrite for com.apple.mail");
+        assert_eq!(body["messages"][1]["content"], "hey team");
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        let mut settings = PipelineSettings::default();
+        settings.pipelines[0].steps.push(step(StepKind::Regex {
+            pattern: "[".to_string(),
+            replacement: String::new(),
+        }));
+        assert!(settings.validate().is_err());
+
+        let settings = PipelineSettings {
+            active_pipeline: "missing".to_string(),
+            ..PipelineSettings::default()
+        };
+        assert!(settings.validate().is_err());
+
+        let mut settings = PipelineSettings::default();
+        settings.pipelines[0].steps.push(step(StepKind::Normalize {
+            locale: Some("xx-XX".to_string()),
+        }));
+        assert!(settings.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn normalizes_in_the_dictation_locale() {
+        let english = run(
+            vec![step(StepKind::Normalize { locale: None })],
+            "twenty five dollars at three pm",
+        )
+        .await;
+        assert_eq!(english.output, "$25 at 3 PM");
+
+        let german = run(
+            vec![step(StepKind::Normalize {
+                locale: Some("de-DE".to_string()),
+            })],
+            "fünfundzwanzig Euro",
+        )
+        .await;
+        assert_eq!(german.output, "25 €");
+    }
+
+    #[test]
+    fn parses_steps_from_json() {
+        let steps: Vec<PipelineStep> = serde_json::from_str(
+            r#"[
+                {"type": "findReplace", "find": "a", "replace": "b"},
+                {"type": "trim", "enabled": false},
+                {"type": "case", "mode": "title"}
+            ]"#,
+        )
+        .unwrap();
+        assert!(!steps[1].enabled);
+        assert_eq!(
+            steps[0].kind,
+            StepKind::FindReplace {
+                find: "a".to_string(),
+                replace: "b".to_string(),
+                ignore_case: false
+            }
+        );
+    }
+}
@@ -0,0 +1,524 @@
+use crate::overlay::{OverlayManager, OverlayState};
+use crate::recorder::AppData;
+use crate::store::{JsonStore, Validate};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tracing::{debug, error, info, warn};
+
+const PUSH_TO_TALK_FILE: &str = "push_to_talk.json";
+
+/// How the dictation shortcut drives the recorder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PushToTalkMode {
+    /// Record while the shortcut is held down
+    Hold,
+    /// Each press starts or stops a recording
+    Toggle,
+    /// Hold to talk, or tap twice quickly to keep recording until the next press
+    DoubleTapLock,
+}
+
+/// Push-to-talk settings - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushToTalkSettings {
+    /// Accelerator string, e.g. "CommandOrControl+Shift+Space"
+    pub shortcut: String,
+    pub mode: PushToTalkMode,
+    /// Recording device used when no session is open yet
+    pub device_name: String,
+    /// Maximum gap between taps, and maximum tap length, for double-tap-to-lock
+    #[serde(default = "default_double_tap_window_ms")]
+    pub double_tap_window_ms: u64,
+}
+
+fn default_double_tap_window_ms() -> u64 {
+    300
+}
+
+// Saved as `null` while push-to-talk is turned off
+impl Validate for Option<PushToTalkSettings> {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(settings) = self {
+            settings
+                .shortcut
+                .parse::<Shortcut>()
+                .map_err(|e| format!("Invalid shortcut '{}': {}", settings.shortcut, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Events forwarded from the shortcut handler to the push-to-talk worker
+#[derive(Debug)]
+enum PushToTalkEvent {
+    Pressed,
+    Released,
+    Shutdown,
+}
+
+/// Where the worker is in a push-to-talk gesture
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    /// Not recording
+    Idle,
+    /// Recording while the key is held
+    Holding { since: Instant },
+    /// Recording after a short tap, waiting to see if a second tap locks it
+    AwaitingSecondTap { deadline: Instant },
+    /// Recording hands-free until the next press
+    Latched,
+}
+
+/// Side effect the worker should perform after a transition
+#[derive(Debug, PartialEq)]
+enum Action {
+    None,
+    Start,
+    Stop,
+}
+
+/// Advance the gesture state machine for one key event
+fn step(
+    mode: PushToTalkMode,
+    double_tap_window: Duration,
+    phase: Phase,
+    event: &PushToTalkEvent,
+    now: Instant,
+) -> (Phase, Action) {
+    use PushToTalkEvent::{Pressed, Released};
+
+    match (mode, phase, event) {
+        (_, Phase::Idle, Pressed) => match mode {
+            PushToTalkMode::Toggle => (Phase::Latched, Action::Start),
+            _ => (Phase::Holding { since: now }, Action::Start),
+        },
+        (_, Phase::Latched, Pressed) => (Phase::Idle, Action::Stop),
+
+        (PushToTalkMode::Hold, Phase::Holding { .. }, Released) => (Phase::Idle, Action::Stop),
+        (PushToTalkMode::DoubleTapLock, Phase::Holding { since }, Released) => {
+            if now.duration_since(since) < double_tap_window {
+                (
+                    Phase::AwaitingSecondTap {
+                        deadline: now + double_tap_window,
+                    },
+                    Action::None,
+                )
+            } else {
+                (Phase::Idle, Action::Stop)
+            }
+        }
+        (_, Phase::AwaitingSecondTap { .. }, Pressed) => (Phase::Latched, Action::None),
+
+        (_, phase, _) => (phase, Action::None),
+    }
+}
+
+/// Registered shortcut and the channel to its worker thread
+struct Registration {
+    shortcut: Shortcut,
+    event_tx: Sender<PushToTalkEvent>,
+}
+
+/// Owns the natively registered dictation shortcut and remembers it across restarts
+pub struct PushToTalk {
+    settings: Option<PushToTalkSettings>,
+    registration: Option<Registration>,
+    saved: JsonStore<Option<PushToTalkSettings>>,
+}
+
+impl Default for PushToTalk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PushToTalk {
+    pub fn new() -> Self {
+        Self {
+            settings: None,
+            registration: None,
+            saved: JsonStore::default(),
+        }
+    }
+
+    /// Load the saved settings from the given app data directory
+    pub fn load(&mut self, app_dir: PathBuf) {
+        self.saved = JsonStore::load(app_dir.join(PUSH_TO_TALK_FILE));
+    }
+
+    /// Register the saved shortcut, if push-to-talk was set up before
+    pub fn restore(&mut self, app: &AppHandle) {
+        let Some(settings) = self.saved.get().clone() else {
+            return;
+        };
+        if let Err(e) = self.register(app, settings) {
+            warn!("Failed to restore push-to-talk shortcut: {}", e);
+        }
+    }
+
+    /// Register the dictation shortcut, replacing any previous registration,
+    /// and save it so it is registered again on the next start
+    pub fn register(
+        &mut self,
+        app: &AppHandle,
+        settings: PushToTalkSettings,
+    ) -> Result<(), String> {
+        self.release(app)?;
+
+        let shortcut: Shortcut = settings
+            .shortcut
+            .parse()
+            .map_err(|e| format!("Invalid shortcut '{}': {}", settings.shortcut, e))?;
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let worker_app = app.clone();
+        let worker_settings = settings.clone();
+        std::thread::Builder::new()
+            .name("push-to-talk".to_string())
+            .spawn(move || run_worker(worker_app, worker_settings, event_rx))
+            .map_err(|e| format!("Failed to spawn push-to-talk thread: {}", e))?;
+
+        // Only forward events from the handler; the worker does the blocking work
+        let handler_tx = event_tx.clone();
+        let registered =
+            app.global_shortcut()
+                .on_shortcut(shortcut, move |_app, _shortcut, event| {
+                    let event = match event.state() {
+                        ShortcutState::Pressed => PushToTalkEvent::Pressed,
+                        ShortcutState::Released => PushToTalkEvent::Released,
+                    };
+                    if let Err(e) = handler_tx.send(event) {
+                        error!("Failed to forward push-to-talk event: {}", e);
+                    }
+                });
+
+        if let Err(e) = registered {
+            let _ = event_tx.send(PushToTalkEvent::Shutdown);
+            return Err(format!(
+                "Failed to register shortcut '{}': {}",
+                settings.shortcut, e
+            ));
+        }
+
+        info!(
+            "Registered push-to-talk shortcut '{}' ({:?})",
+            settings.shortcut, settings.mode
+        );
+        self.registration = Some(Registration { shortcut, event_tx });
+        self.settings = Some(settings.clone());
+        self.saved.set(Some(settings))
+    }
+
+    /// Unregister the dictation shortcut and forget it
+    pub fn unregister(&mut self, app: &AppHandle) -> Result<(), String> {
+        self.release(app)?;
+        self.saved.set(None)
+    }
+
+    /// Unregister the dictation shortcut and stop its worker
+    fn release(&mut self, app: &AppHandle) -> Result<(), String> {
+        let Some(registration) = self.registration.take() else {
+            return Ok(());
+        };
+
+        let _ = registration.event_tx.send(PushToTalkEvent::Shutdown);
+        app.global_shortcut()
+            .unregister(registration.shortcut)
+            .map_err(|e| format!("Failed to unregister shortcut: {}", e))?;
+
+        self.settings = None;
+        info!("Unregistered push-to-talk shortcut");
+        Ok(())
+    }
+
+    pub fn settings(&self) -> Option<&PushToTalkSettings> {
+        self.settings.as_ref()
+    }
+}
+
+/// Process key events in order, driving the recorder and overlay
+fn run_worker(app: AppHandle, settings: PushToTalkSettings, event_rx: Receiver<PushToTalkEvent>) {
+    let double_tap_window = Duration::from_millis(settings.double_tap_window_ms);
+    let mut phase = Phase::Idle;
+    // Auto-repeat sends repeated presses while the key is held; only the first counts
+    let mut key_down = false;
+
+    loop {
+        let event = match phase {
+            Phase::AwaitingSecondTap { deadline } => {
+                match event_rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => {
+                        debug!("No second tap, stopping push-to-talk recording");
+                        stop_dictation(&app);
+                        phase = Phase::Idle;
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            _ => match event_rx.recv() {
+                Ok(event) => event,
+                Err(_) => break,
+            },
+        };
+
+        match event {
+            PushToTalkEvent::Shutdown => break,
+            PushToTalkEvent::Pressed if key_down => continue,
+            PushToTalkEvent::Pressed => key_down = true,
+            PushToTalkEvent::Released => key_down = false,
+        }
+
+        let (next, action) = step(
+            settings.mode,
+            double_tap_window,
+            phase,
+            &event,
+            Instant::now(),
+        );
+        debug!("Push-to-talk {:?}: {:?} -> {:?}", event, phase, next);
+
+        phase = match action {
+            Action::Start => match start_dictation(&app, &settings) {
+                Ok(()) => next,
+                Err(e) => {
+                    error!("Failed to start push-to-talk recording: {}", e);
+                    let _ = app.emit("push-to-talk-error", &e);
+                    Phase::Idle
+                }
+            },
+            Action::Stop => {
+                stop_dictation(&app);
+                next
+            }
+            Action::None => next,
+        };
+    }
+
+    debug!("Push-to-talk worker terminated");
+}
+
+/// Open a session if needed, start recording and show the overlay
+fn start_dictation(app: &AppHandle, settings: &PushToTalkSettings) -> Result<(), String> {
+    {
+        let data = app.state::<AppData>();
+        let mut audio_manager = data.audio_manager.lock().map_err(|e| e.to_string())?;
+        let device = Some(settings.device_name.as_str()).filter(|name| !name.is_empty());
+        audio_manager
+            .ensure_session(device)
+            .map_err(|e| e.to_string())?;
+        audio_manager.start_recording().map_err(|e| e.to_string())?;
+    }
+
+    with_overlay(app, |overlay| overlay.show_overlay(OverlayState::Recording));
+    let _ = app.emit("push-to-talk-started", ());
+    Ok(())
+}
+
+/// Stop recording and hand the audio to the app for processing
+fn stop_dictation(app: &AppHandle) {
+    let recording = {
+        let data = app.state::<AppData>();
+        let mut audio_manager = match data.audio_manager.lock() {
+            Ok(audio_manager) => audio_manager,
+            Err(e) => {
+                error!("Failed to lock audio manager: {}", e);
+                return;
+            }
+        };
+        audio_manager.stop_recording()
+    };
+
+    match recording {
+        Ok(recording) => {
+            with_overlay(app, |overlay| {
+                overlay.update_state(OverlayState::Processing)
+            });
+            if let Err(e) = app.emit("push-to-talk-recorded", &recording) {
+                error!("Failed to emit push-to-talk recording: {}", e);
+            }
+        }
+        Err(e) => {
+            error!("Failed to stop push-to-talk recording: {}", e);
+            with_overlay(app, |overlay| overlay.hide_overlay());
+            let _ = app.emit("push-to-talk-error", e.to_string());
+        }
+    }
+}
+
+fn with_overlay(app: &AppHandle, f: impl FnOnce(&mut OverlayManager) -> tauri::Result<()>) {
+    let Some(overlay) = app.try_state::<Mutex<OverlayManager>>() else {
+        return;
+    };
+    let result = match overlay.lock() {
+        Ok(mut overlay) => f(&mut overlay),
+        Err(e) => {
+            warn!("Failed to lock overlay manager: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = result {
+        warn!("Failed to update overlay: {}", e);
+    }
+}
+
+/// Register the dictation shortcut natively
+#[tauri::command]
+pub async fn register_push_to_talk(
+    app: AppHandle,
+    settings: PushToTalkSettings,
+    state: State<'_, Mutex<PushToTalk>>,
+) -> Result<(), String> {
+    let mut push_to_talk = state.lock().map_err(|e| e.to_string())?;
+    push_to_talk.register(&app, settings)
+}
+
+/// Unregister the native dictation shortcut
+#[tauri::command]
+pub async fn unregister_push_to_talk(
+    app: AppHandle,
+    state: State<'_, Mutex<PushToTalk>>,
+) -> Result<(), String> {
+    let mut push_to_talk = state.lock().map_err(|e| e.to_string())?;
+    push_to_talk.unregister(&app)
+}
+
+/// Get the currently registered push-to-talk settings, if any
+#[tauri::command]
+pub async fn get_push_to_talk_settings(
+    state: State<'_, Mutex<PushToTalk>>,
+) -> Result<Option<PushToTalkSettings>, String> {
+    let push_to_talk = state.lock().map_err(|e| e.to_string())?;
+    Ok(push_to_talk.settings().cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PushToTalkEvent::{Pressed, Released};
+
+    const WINDOW: Duration = Duration::from_millis(300);
+
+    /// Feed events spaced by the given milliseconds, collecting the actions
+    fn run(mode: PushToTalkMode, events: &[(u64, PushToTalkEvent)]) -> (Phase, Vec<Action>) {
+        let mut now = Instant::now();
+        let mut phase = Phase::Idle;
+        let mut actions = Vec::new();
+        for (after_ms, event) in events {
+            now += Duration::from_millis(*after_ms);
+            let (next, action) = step(mode, WINDOW, phase, event, now);
+            phase = next;
+            actions.push(action);
+        }
+        (phase, actions)
+    }
+
+    #[test]
+    fn hold_records_while_pressed() {
+        let (phase, actions) = run(PushToTalkMode::Hold, &[(0, Pressed), (50, Released)]);
+        assert_eq!(phase, Phase::Idle);
+        assert_eq!(actions, vec![Action::Start, Action::Stop]);
+    }
+
+    #[test]
+    fn toggle_starts_and_stops_on_presses() {
+        let (phase, actions) = run(
+            PushToTalkMode::Toggle,
+            &[
+                (0, Pressed),
+                (50, Released),
+                (2000, Pressed),
+                (50, Released),
+            ],
+        );
+        assert_eq!(phase, Phase::Idle);
+        assert_eq!(
+            actions,
+            vec![Action::Start, Action::None, Action::Stop, Action::None]
+        );
+    }
+
+    #[test]
+    fn double_tap_locks_until_next_press() {
+        let (phase, actions) = run(
+            PushToTalkMode::DoubleTapLock,
+            &[
+                (0, Pressed),
+                (100, Released),
+                (100, Pressed),
+                (50, Released),
+            ],
+        );
+        assert_eq!(phase, Phase::Latched);
+        assert_eq!(
+            actions,
+            vec![Action::Start, Action::None, Action::None, Action::None]
+        );
+
+        let (next, action) = step(
+            PushToTalkMode::DoubleTapLock,
+            WINDOW,
+            phase,
+            &Pressed,
+            Instant::now(),
+        );
+        assert_eq!((next, action), (Phase::Idle, Action::Stop));
+    }
+
+    #[test]
+    fn double_tap_mode_holds_like_hold() {
+        let (phase, actions) = run(
+            PushToTalkMode::DoubleTapLock,
+            &[(0, Pressed), (1000, Released)],
+        );
+        assert_eq!(phase, Phase::Idle);
+        assert_eq!(actions, vec![Action::Start, Action::Stop]);
+    }
+
+    #[test]
+    fn short_tap_waits_for_second_tap() {
+        let start = Instant::now();
+        let (phase, _) = step(
+            PushToTalkMode::DoubleTapLock,
+            WINDOW,
+            Phase::Holding { since: start },
+            &Released,
+            start + Duration::from_millis(100),
+        );
+        assert_eq!(
+            phase,
+            Phase::AwaitingSecondTap {
+                deadline: start + Duration::from_millis(400)
+            }
+        );
+    }
+
+    #[test]
+    fn ignores_stray_events() {
+        for mode in [
+            PushToTalkMode::Hold,
+            PushToTalkMode::Toggle,
+            PushToTalkMode::DoubleTapLock,
+        ] {
+            let (phase, actions) = run(mode, &[(0, Released)]);
+            assert_eq!(phase, Phase::Idle);
+            assert_eq!(actions, vec![Action::None]);
+        }
+        let (phase, action) = step(
+            PushToTalkMode::Hold,
+            WINDOW,
+            Phase::Latched,
+            &Released,
+            Instant::now(),
+        );
+        assert_eq!((phase, action), (Phase::Latched, Action::None));
+    }
+}
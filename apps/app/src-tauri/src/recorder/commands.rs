@@ -1,4 +1,7 @@
+use crate::recorder::cues::{SoundCue, SoundCueSettings};
+use crate::recorder::input::InputSettings;
 use crate::recorder::manager::{AudioManager, DeviceInfo, Result};
+use crate::recorder::thread::SessionPolicy;
 use crate::recorder::{AudioRecording, RecorderError};
 use std::sync::Mutex;
 use tauri::State;
@@ -34,13 +37,26 @@ pub async fn enumerate_recording_devices(state: State<'_, AppData>) -> Result<Ve
 }
 
 #[tauri::command]
-pub async fn init_recording_session(device_name: String, state: State<'_, AppData>) -> Result<()> {
+pub async fn init_recording_session(
+    device_name: String,
+    input_settings: Option<InputSettings>,
+    state: State<'_, AppData>,
+) -> Result<()> {
     info!(
         "Starting init_recording_session with device_name: {}",
         device_name
     );
     let mut audio_manager = get_audio_manager(&state)?;
-    audio_manager.init_recording_session(device_name)
+    audio_manager.init_recording_session(device_name, input_settings)
+}
+
+#[tauri::command]
+pub async fn get_device_input_settings(
+    device_name: String,
+    state: State<'_, AppData>,
+) -> Result<InputSettings> {
+    let audio_manager = get_audio_manager(&state)?;
+    Ok(audio_manager.get_device_input_settings(&device_name))
 }
 
 #[tauri::command]
@@ -74,3 +90,38 @@ pub async fn cancel_recording(state: State<'_, AppData>) -> Result<()> {
     let mut audio_manager = get_audio_manager(&state)?;
     audio_manager.cancel_recording()
 }
+
+#[tauri::command]
+pub async fn get_sound_cue_settings(state: State<'_, AppData>) -> Result<SoundCueSettings> {
+    let audio_manager = get_audio_manager(&state)?;
+    Ok(audio_manager.get_sound_cue_settings())
+}
+
+#[tauri::command]
+pub async fn set_sound_cue_settings(
+    settings: SoundCueSettings,
+    state: State<'_, AppData>,
+) -> Result<()> {
+    debug!("Updating sound cue settings");
+    let mut audio_manager = get_audio_manager(&state)?;
+    audio_manager.set_sound_cue_settings(settings)
+}
+
+#[tauri::command]
+pub async fn preview_sound_cue(cue: SoundCue, state: State<'_, AppData>) -> Result<()> {
+    let mut audio_manager = get_audio_manager(&state)?;
+    audio_manager.preview_sound_cue(cue);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_session_policy(state: State<'_, AppData>) -> Result<SessionPolicy> {
+    let audio_manager = get_audio_manager(&state)?;
+    Ok(audio_manager.get_session_policy())
+}
+
+#[tauri::command]
+pub async fn set_session_policy(policy: SessionPolicy, state: State<'_, AppData>) -> Result<()> {
+    let mut audio_manager = get_audio_manager(&state)?;
+    audio_manager.set_session_policy(policy)
+}
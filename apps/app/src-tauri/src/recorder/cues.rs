@@ -0,0 +1,502 @@
+use crate::store::{JsonStore, Validate};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Extra time a cue is treated as audible after it ends, covering output latency
+const CUE_TAIL_GUARD: Duration = Duration::from_millis(80);
+
+const SOUND_CUE_SETTINGS_FILE: &str = "sound_cues.json";
+
+const BUNDLED_START: &[u8] = include_bytes!("../../sounds/start.wav");
+const BUNDLED_STOP: &[u8] = include_bytes!("../../sounds/stop.wav");
+const BUNDLED_CANCEL: &[u8] = include_bytes!("../../sounds/cancel.wav");
+const BUNDLED_ERROR: &[u8] = include_bytes!("../../sounds/error.wav");
+
+/// Moments in the recording lifecycle that can play a cue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SoundCue {
+    Start,
+    Stop,
+    Cancel,
+    Error,
+}
+
+/// Where the audio for a single cue comes from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CueSource {
+    /// The WAV file shipped with the app
+    Bundled,
+    /// A user-supplied WAV file on disk
+    File { path: String },
+    /// Play nothing for this cue
+    Off,
+}
+
+/// Sound cue settings - matches TypeScript interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SoundCueSettings {
+    pub enabled: bool,
+    /// Linear playback volume between 0.0 and 1.0
+    pub volume: f32,
+    pub start: CueSource,
+    pub stop: CueSource,
+    pub cancel: CueSource,
+    pub error: CueSource,
+}
+
+impl Default for SoundCueSettings {
+    fn default() -> Self {
+        Self {
+            // The webview still plays its own cues until it hands them over
+            enabled: false,
+            volume: 0.6,
+            start: CueSource::Bundled,
+            stop: CueSource::Bundled,
+            cancel: CueSource::Bundled,
+            error: CueSource::Bundled,
+        }
+    }
+}
+
+impl Validate for SoundCueSettings {}
+
+impl SoundCueSettings {
+    fn source(&self, cue: SoundCue) -> &CueSource {
+        match cue {
+            SoundCue::Start => &self.start,
+            SoundCue::Stop => &self.stop,
+            SoundCue::Cancel => &self.cancel,
+            SoundCue::Error => &self.error,
+        }
+    }
+}
+
+/// Decoded cue audio, mono f32 at its native sample rate
+#[derive(Debug)]
+struct CueSamples {
+    samples: Vec<f32>,
+    sample_rate: u32,
+}
+
+impl CueSamples {
+    fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.samples.len() as f32 / self.sample_rate as f32)
+    }
+}
+
+/// Plays short start/stop/cancel/error cues on the default output device.
+///
+/// The start cue plays to the end before capture begins, so it never overlaps
+/// the user's first words. Other cues play on their own short-lived thread so
+/// callers never block on audio output; while one is audible the shared capture
+/// gate is raised, and a running input stream writes silence instead of
+/// microphone samples so the cue never ends up in the recording.
+pub struct SoundCuePlayer {
+    store: JsonStore<SoundCueSettings>,
+    cache: HashMap<SoundCue, Arc<CueSamples>>,
+    capture_gate: Arc<AtomicUsize>,
+}
+
+impl Default for SoundCuePlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoundCuePlayer {
+    pub fn new() -> Self {
+        Self {
+            store: JsonStore::default(),
+            cache: HashMap::new(),
+            capture_gate: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Counter that is non-zero while any cue is playing
+    pub fn capture_gate(&self) -> Arc<AtomicUsize> {
+        self.capture_gate.clone()
+    }
+
+    /// Load saved settings from the given app data directory
+    pub fn load(&mut self, app_dir: PathBuf) {
+        self.store = JsonStore::load(app_dir.join(SOUND_CUE_SETTINGS_FILE));
+        self.cache.clear();
+        info!(
+            "Loaded sound cue settings (enabled: {})",
+            self.settings().enabled
+        );
+    }
+
+    pub fn settings(&self) -> &SoundCueSettings {
+        self.store.get()
+    }
+
+    /// Replace the settings, validating any custom files up front
+    pub fn set_settings(&mut self, settings: SoundCueSettings) -> Result<(), String> {
+        for cue in [
+            SoundCue::Start,
+            SoundCue::Stop,
+            SoundCue::Cancel,
+            SoundCue::Error,
+        ] {
+            if let CueSource::File { path } = settings.source(cue) {
+                let bytes = std::fs::read(path)
+                    .map_err(|e| format!("Failed to read sound cue '{}': {}", path, e))?;
+                decode_wav(&bytes).map_err(|e| format!("Invalid sound cue '{}': {}", path, e))?;
+            }
+        }
+
+        self.cache.clear();
+        self.store.set(SoundCueSettings {
+            volume: settings.volume.clamp(0.0, 1.0),
+            ..settings
+        })?;
+        info!(
+            "Sound cue settings updated (enabled: {})",
+            self.settings().enabled
+        );
+        Ok(())
+    }
+
+    /// Play a cue if cues are enabled and wait until it has finished, so capture
+    /// started afterwards neither records the cue nor loses speech to it
+    pub fn play_and_wait(&mut self, cue: SoundCue) {
+        if !self.settings().enabled {
+            return;
+        }
+        let samples = match self.samples(cue) {
+            Ok(Some(samples)) => samples,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to load {:?} sound cue: {}", cue, e);
+                return;
+            }
+        };
+        if let Err(e) = play_blocking(&samples, self.settings().volume) {
+            error!("Failed to play {:?} sound cue: {}", cue, e);
+            return;
+        }
+        std::thread::sleep(CUE_TAIL_GUARD);
+    }
+
+    /// Play a cue if cues are enabled, logging rather than failing on errors
+    pub fn play(&mut self, cue: SoundCue) {
+        if !self.settings().enabled {
+            return;
+        }
+        self.play_unchecked(cue);
+    }
+
+    /// Play a cue regardless of the enabled flag, e.g. for a settings preview
+    pub fn play_unchecked(&mut self, cue: SoundCue) {
+        let samples = match self.samples(cue) {
+            Ok(Some(samples)) => samples,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to load {:?} sound cue: {}", cue, e);
+                return;
+            }
+        };
+
+        let volume = self.settings().volume;
+        let capture_gate = self.capture_gate.clone();
+
+        // Raise the gate before the thread starts so a capture starting right
+        // after this call is already muted
+        capture_gate.fetch_add(1, Ordering::AcqRel);
+
+        let spawned = std::thread::Builder::new()
+            .name("sound-cue".to_string())
+            .spawn({
+                let capture_gate = capture_gate.clone();
+                move || {
+                    if let Err(e) = play_blocking(&samples, volume) {
+                        error!("Failed to play {:?} sound cue: {}", cue, e);
+                    }
+                    std::thread::sleep(CUE_TAIL_GUARD);
+                    capture_gate.fetch_sub(1, Ordering::AcqRel);
+                }
+            });
+
+        if let Err(e) = spawned {
+            error!("Failed to spawn sound cue thread: {}", e);
+            capture_gate.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    fn samples(&mut self, cue: SoundCue) -> Result<Option<Arc<CueSamples>>, String> {
+        if let Some(samples) = self.cache.get(&cue) {
+            return Ok(Some(samples.clone()));
+        }
+
+        let samples = match self.settings().source(cue) {
+            CueSource::Off => return Ok(None),
+            CueSource::Bundled => decode_wav(bundled_bytes(cue))?,
+            CueSource::File { path } => {
+                let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+                decode_wav(&bytes)?
+            }
+        };
+
+        let samples = Arc::new(samples);
+        self.cache.insert(cue, samples.clone());
+        Ok(Some(samples))
+    }
+}
+
+fn bundled_bytes(cue: SoundCue) -> &'static [u8] {
+    match cue {
+        SoundCue::Start => BUNDLED_START,
+        SoundCue::Stop => BUNDLED_STOP,
+        SoundCue::Cancel => BUNDLED_CANCEL,
+        SoundCue::Error => BUNDLED_ERROR,
+    }
+}
+
+/// Decode a WAV file into mono f32 samples
+fn decode_wav(bytes: &[u8]) -> Result<CueSamples, String> {
+    let reader = hound::WavReader::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let spec = reader.spec();
+    if spec.sample_rate == 0 || spec.channels == 0 {
+        return Err(format!(
+            "Unsupported WAV format: {} Hz, {} channels",
+            spec.sample_rate, spec.channels
+        ));
+    }
+    let channels = spec.channels as usize;
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.to_string())?
+        }
+    };
+
+    // Downmix to mono by averaging each frame
+    let samples = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+
+    Ok(CueSamples {
+        samples,
+        sample_rate: spec.sample_rate,
+    })
+}
+
+/// Play samples on the default output device and block until they have finished
+fn play_blocking(cue: &CueSamples, volume: f32) -> Result<(), String> {
+    let host = cpal::default_host();
+    let device = host
+        .default_output_device()
+        .ok_or_else(|| "No default output device available".to_string())?;
+    let config = device.default_output_config().map_err(|e| e.to_string())?;
+
+    let sample_format = config.sample_format();
+    let stream_config: cpal::StreamConfig = config.into();
+    let output = resample(
+        &cue.samples,
+        cue.sample_rate,
+        stream_config.sample_rate.0,
+        volume,
+    );
+
+    let stream = match sample_format {
+        SampleFormat::F32 => build_output_stream::<f32>(&device, &stream_config, output),
+        SampleFormat::I16 => build_output_stream::<i16>(&device, &stream_config, output),
+        SampleFormat::U16 => build_output_stream::<u16>(&device, &stream_config, output),
+        other => return Err(format!("Unsupported output sample format: {:?}", other)),
+    }
+    .map_err(|e| e.to_string())?;
+
+    stream.play().map_err(|e| e.to_string())?;
+    std::thread::sleep(cue.duration());
+    debug!("Sound cue finished playing");
+    Ok(())
+}
+
+fn build_output_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    samples: Vec<f32>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let mut position = 0;
+    let err_fn = |err| error!("Error in sound cue stream: {}", err);
+
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &_| {
+            for frame in data.chunks_mut(channels) {
+                let value = samples.get(position).copied().unwrap_or(0.0);
+                position += 1;
+                for out in frame.iter_mut() {
+                    *out = T::from_sample(value);
+                }
+            }
+        },
+        err_fn,
+        None,
+    )
+}
+
+/// Linear resampling with volume applied, good enough for short UI cues
+fn resample(samples: &[f32], from_rate: u32, to_rate: u32, volume: f32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.iter().map(|s| s * volume).collect();
+    }
+
+    let ratio = from_rate as f64 / to_rate as f64;
+    let out_len = (samples.len() as f64 / ratio) as usize;
+    (0..out_len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let index = pos as usize;
+            let frac = (pos - index as f64) as f32;
+            let a = samples[index.min(samples.len() - 1)];
+            let b = samples[(index + 1).min(samples.len() - 1)];
+            (a + (b - a) * frac) * volume
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav_bytes(
+        spec: hound::WavSpec,
+        write: impl FnOnce(&mut hound::WavWriter<&mut Cursor<Vec<u8>>>),
+    ) -> Vec<u8> {
+        let mut cursor = Cursor::new(Vec::new());
+        {
+            let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+            write(&mut writer);
+            writer.finalize().unwrap();
+        }
+        cursor.into_inner()
+    }
+
+    #[test]
+    fn decodes_and_downmixes_int_wav() {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 22050,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let bytes = wav_bytes(spec, |writer| {
+            for sample in [16384i16, -16384, 16384, 16384, i16::MIN, i16::MIN] {
+                writer.write_sample(sample).unwrap();
+            }
+        });
+
+        let cue = decode_wav(&bytes).unwrap();
+        assert_eq!(cue.sample_rate, 22050);
+        assert_eq!(cue.samples, vec![0.0, 0.5, -1.0]);
+    }
+
+    #[test]
+    fn decodes_float_wav() {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 48000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let bytes = wav_bytes(spec, |writer| {
+            for sample in [0.25f32, -0.75] {
+                writer.write_sample(sample).unwrap();
+            }
+        });
+
+        let cue = decode_wav(&bytes).unwrap();
+        assert_eq!(cue.sample_rate, 48000);
+        assert_eq!(cue.samples, vec![0.25, -0.75]);
+        assert_eq!(cue.duration(), Duration::from_secs_f32(2.0 / 48000.0));
+    }
+
+    #[test]
+    fn decodes_bundled_cues() {
+        for cue in [
+            SoundCue::Start,
+            SoundCue::Stop,
+            SoundCue::Cancel,
+            SoundCue::Error,
+        ] {
+            let samples = decode_wav(bundled_bytes(cue)).unwrap();
+            assert!(!samples.samples.is_empty(), "{:?}", cue);
+            assert!(samples.duration() < Duration::from_millis(500), "{:?}", cue);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_wav() {
+        assert!(decode_wav(b"not a wav file").is_err());
+    }
+
+    #[test]
+    fn rejects_wav_without_rate_or_channels() {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let bytes = wav_bytes(spec, |writer| writer.write_sample(0i16).unwrap());
+
+        // Patch the channel count, or the sample and byte rates, in the `fmt ` chunk
+        let mut no_channels = bytes.clone();
+        no_channels[22..24].copy_from_slice(&0u16.to_le_bytes());
+        assert!(decode_wav(&no_channels).is_err());
+
+        let mut no_rate = bytes;
+        no_rate[24..32].copy_from_slice(&[0; 8]);
+        let error = decode_wav(&no_rate).err().unwrap();
+        assert!(error.contains("0 Hz"), "{}", error);
+    }
+
+    #[test]
+    fn resample_applies_volume_at_same_rate() {
+        assert_eq!(resample(&[1.0, -0.5], 16000, 16000, 0.5), vec![0.5, -0.25]);
+        assert!(resample(&[], 16000, 48000, 1.0).is_empty());
+    }
+
+    #[test]
+    fn resample_interpolates_when_upsampling() {
+        assert_eq!(
+            resample(&[0.0, 1.0, 0.0], 1, 2, 1.0),
+            vec![0.0, 0.5, 1.0, 0.5, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn resample_drops_samples_when_downsampling() {
+        assert_eq!(
+            resample(&[0.0, 0.2, 0.4, 0.6, 0.8, 1.0], 48000, 16000, 1.0),
+            vec![0.0, 0.6]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use crate::store::{JsonStore, Validate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+const DEVICE_SETTINGS_FILE: &str = "device_settings.json";
+
+/// Per-device capture settings - matches TypeScript interface
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InputSettings {
+    /// Zero-based input channels to capture, mixed down to mono.
+    /// Empty means record every channel interleaved.
+    pub channels: Vec<u16>,
+    /// Software gain applied to captured samples, in dB
+    pub gain_db: f32,
+}
+
+impl InputSettings {
+    /// Minimum number of device channels needed to honor the selection
+    pub fn required_channels(&self) -> u16 {
+        self.channels.iter().max().map_or(1, |&max| max + 1)
+    }
+}
+
+/// Channel selection and gain resolved against an opened stream
+#[derive(Debug, Clone)]
+pub struct InputProcessing {
+    device_channels: usize,
+    selected: Vec<usize>,
+    gain: f32,
+}
+
+impl InputProcessing {
+    pub fn new(settings: &InputSettings, device_channels: u16) -> Self {
+        let device_channels = device_channels.max(1) as usize;
+        let selected: Vec<usize> = settings
+            .channels
+            .iter()
+            .map(|&c| c as usize)
+            .filter(|&c| c < device_channels)
+            .collect();
+
+        if selected.len() < settings.channels.len() {
+            warn!(
+                "Ignoring input channels outside the device's {} channels",
+                device_channels
+            );
+        }
+
+        Self {
+            device_channels,
+            selected,
+            gain: 10f32.powf(settings.gain_db / 20.0),
+        }
+    }
+
+    /// Number of channels written to the recording buffer
+    pub fn output_channels(&self) -> u16 {
+        if self.selected.is_empty() {
+            self.device_channels as u16
+        } else {
+            1
+        }
+    }
+
+    /// Append one callback's worth of interleaved samples to the buffer.
+    /// When `muted` is set the timeline is kept but silence is written instead.
+    pub fn extend<T: Copy>(
+        &self,
+        buffer: &mut Vec<f32>,
+        data: &[T],
+        convert: impl Fn(T) -> f32,
+        muted: bool,
+    ) {
+        let frames = data.len() / self.device_channels;
+        let new_samples = frames * self.output_channels() as usize;
+
+        // Reserve space for new samples to avoid frequent reallocations
+        if buffer.capacity() < buffer.len() + new_samples {
+            buffer.reserve(new_samples);
+        }
+
+        if muted {
+            buffer.resize(buffer.len() + new_samples, 0.0);
+            return;
+        }
+
+        let gain = self.gain;
+        if self.selected.is_empty() {
+            buffer.extend(data.iter().map(|&s| (convert(s) * gain).clamp(-1.0, 1.0)));
+            return;
+        }
+
+        let scale = gain / self.selected.len() as f32;
+        buffer.extend(data.chunks_exact(self.device_channels).map(|frame| {
+            let sum: f32 = self.selected.iter().map(|&c| convert(frame[c])).sum();
+            (sum * scale).clamp(-1.0, 1.0)
+        }));
+    }
+}
+
+/// Input settings remembered per recording device, persisted as JSON
+#[derive(Debug, Default)]
+pub struct DeviceSettingsStore {
+    store: JsonStore<HashMap<String, InputSettings>>,
+}
+
+impl Validate for HashMap<String, InputSettings> {}
+
+impl DeviceSettingsStore {
+    /// Load saved settings from the given app data directory
+    pub fn load(&mut self, app_dir: PathBuf) {
+        self.store = JsonStore::load(app_dir.join(DEVICE_SETTINGS_FILE));
+        info!(
+            "Loaded input settings for {} devices",
+            self.store.get().len()
+        );
+    }
+
+    pub fn get(&self, device_name: &str) -> InputSettings {
+        self.store
+            .get()
+            .get(device_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Remember settings for a device and write them to disk
+    pub fn set(&mut self, device_name: &str, settings: InputSettings) -> Result<(), String> {
+        if self.store.get().get(device_name) == Some(&settings) {
+            return Ok(());
+        }
+        let mut devices = self.store.get().clone();
+        devices.insert(device_name.to_string(), settings);
+        self.store.set(devices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn processing(channels: &[u16], gain_db: f32, device_channels: u16) -> InputProcessing {
+        let settings = InputSettings {
+            channels: channels.to_vec(),
+            gain_db,
+        };
+        InputProcessing::new(&settings, device_channels)
+    }
+
+    #[test]
+    fn required_channels() {
+        assert_eq!(InputSettings::default().required_channels(), 1);
+        assert_eq!(processing(&[], 0.0, 2).output_channels(), 2);
+        let settings = InputSettings {
+            channels: vec![0, 3],
+            gain_db: 0.0,
+        };
+        assert_eq!(settings.required_channels(), 4);
+    }
+
+    #[test]
+    fn keeps_all_channels_interleaved() {
+        let mut buffer = vec![0.5];
+        processing(&[], 0.0, 2).extend(&mut buffer, &[0.1, 0.2, 0.3, 0.4], |s| s, false);
+        assert_eq!(buffer, vec![0.5, 0.1, 0.2, 0.3, 0.4]);
+    }
+
+    #[test]
+    fn mixes_selected_channels_to_mono() {
+        let mut buffer = Vec::new();
+        let input = processing(&[0, 2], 0.0, 3);
+        assert_eq!(input.output_channels(), 1);
+        input.extend(&mut buffer, &[0.2, 0.9, 0.4, -0.2, 0.9, -0.6], |s| s, false);
+        assert_eq!(buffer.len(), 2);
+        assert!((buffer[0] - 0.3).abs() < 1e-6, "{:?}", buffer);
+        assert!((buffer[1] + 0.4).abs() < 1e-6, "{:?}", buffer);
+    }
+
+    #[test]
+    fn ignores_channels_the_device_lacks() {
+        let mut buffer = Vec::new();
+        let input = processing(&[1, 5], 0.0, 2);
+        input.extend(&mut buffer, &[0.1, 0.8, 0.2, -0.8], |s| s, false);
+        assert_eq!(buffer, vec![0.8, -0.8]);
+    }
+
+    #[test]
+    fn applies_gain_and_clamps() {
+        let mut buffer = Vec::new();
+        // +6 dB roughly doubles the amplitude
+        processing(&[], 6.0, 1).extend(&mut buffer, &[0.25, 0.75, -0.75], |s| s, false);
+        assert!((buffer[0] - 0.5).abs() < 0.01, "{:?}", buffer);
+        assert_eq!(&buffer[1..], &[1.0, -1.0]);
+    }
+
+    #[test]
+    fn converts_samples() {
+        let mut buffer = Vec::new();
+        processing(&[], 0.0, 1).extend(&mut buffer, &[i16::MAX, 0], |s| s as f32 / 32768.0, false);
+        assert!((buffer[0] - 1.0).abs() < 1e-4, "{:?}", buffer);
+        assert_eq!(buffer[1], 0.0);
+    }
+
+    #[test]
+    fn muted_keeps_the_timeline() {
+        let mut buffer = vec![0.5];
+        processing(&[], 0.0, 2).extend(&mut buffer, &[0.1, 0.2, 0.3, 0.4], |s| s, true);
+        assert_eq!(buffer, vec![0.5, 0.0, 0.0, 0.0, 0.0]);
+
+        let mut mono = Vec::new();
+        processing(&[1], 0.0, 2).extend(&mut mono, &[0.1, 0.2, 0.3, 0.4], |s| s, true);
+        assert_eq!(mono, vec![0.0, 0.0]);
+    }
+}
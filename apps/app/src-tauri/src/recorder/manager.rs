@@ -1,11 +1,20 @@
-use crate::recorder::thread::{spawn_audio_thread, AudioCommand, AudioResponse};
+use crate::recorder::cues::{SoundCue, SoundCuePlayer, SoundCueSettings};
+use crate::recorder::input::{DeviceSettingsStore, InputSettings};
+use crate::recorder::thread::{
+    spawn_audio_thread, AudioCommand, AudioResponse, SessionPolicy, StreamErrorEvent,
+    StreamErrorHandler,
+};
 use crate::recorder::RecorderError;
+use crate::store::JsonStore;
 use serde::Serialize;
+use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, RecvError, SendError, Sender};
 use tracing::{debug, error, info};
 
 pub type Result<T> = std::result::Result<T, RecorderError>;
 
+const SESSION_POLICY_FILE: &str = "session_policy.json";
+
 struct AudioThreadHandle {
     command_tx: Sender<AudioCommand>,
     response_rx: Receiver<AudioResponse>,
@@ -14,6 +23,12 @@ struct AudioThreadHandle {
 pub struct AudioManager {
     thread_handle: Option<AudioThreadHandle>,
     is_recording: bool,
+    cues: SoundCuePlayer,
+    device_settings: DeviceSettingsStore,
+    session_policy: JsonStore<SessionPolicy>,
+    /// Device of the last initialized session, reopened on demand
+    selected_device: Option<String>,
+    stream_error_handler: Option<StreamErrorHandler>,
 }
 
 #[derive(Debug, Serialize)]
@@ -46,6 +61,11 @@ impl AudioManager {
         Self {
             thread_handle: None,
             is_recording: false,
+            cues: SoundCuePlayer::new(),
+            device_settings: DeviceSettingsStore::default(),
+            session_policy: JsonStore::default(),
+            selected_device: None,
+            stream_error_handler: None,
         }
     }
 
@@ -86,7 +106,17 @@ impl AudioManager {
 
         debug!("Initializing audio thread...");
         let (response_tx, response_rx) = mpsc::channel();
-        let command_tx = spawn_audio_thread(response_tx)?;
+        let command_tx = spawn_audio_thread(
+            response_tx,
+            self.cues.capture_gate(),
+            self.stream_error_handler.clone(),
+        )?;
+
+        // A fresh thread starts with the default policy, so hand it the current one
+        command_tx.send(AudioCommand::SetSessionPolicy(
+            self.session_policy.get().clone(),
+        ))?;
+        Self::handle_response(response_rx.recv()?, |_| (), "set_session_policy", None)?;
 
         self.thread_handle = Some(AudioThreadHandle {
             command_tx,
@@ -160,14 +190,67 @@ impl AudioManager {
         })
     }
 
-    /// Initialize a recording session with the specified device
-    pub fn init_recording_session(&mut self, device_name: String) -> Result<()> {
+    /// Set the callback for stream errors; applies to audio threads spawned afterwards
+    pub fn set_stream_error_handler(
+        &mut self,
+        handler: impl Fn(&StreamErrorEvent) + Send + Sync + 'static,
+    ) {
+        self.stream_error_handler = Some(std::sync::Arc::new(handler));
+    }
+
+    /// Load the per-device input settings saved in the app data directory
+    pub fn load_device_settings(&mut self, app_dir: PathBuf) {
+        self.device_settings.load(app_dir);
+    }
+
+    /// Load the sound cue settings saved in the app data directory
+    pub fn load_sound_cue_settings(&mut self, app_dir: PathBuf) {
+        self.cues.load(app_dir);
+    }
+
+    /// Load the idle session policy saved in the app data directory
+    pub fn load_session_policy(&mut self, app_dir: PathBuf) {
+        self.session_policy = JsonStore::load(app_dir.join(SESSION_POLICY_FILE));
+        let policy = self.session_policy.get();
+        info!(
+            "Loaded session policy (keep warm: {}, idle timeout: {}s)",
+            policy.keep_warm, policy.idle_timeout_secs
+        );
+    }
+
+    /// Get the saved input settings for a device
+    pub fn get_device_input_settings(&self, device_name: &str) -> InputSettings {
+        self.device_settings.get(device_name)
+    }
+
+    /// Initialize a recording session with the specified device.
+    ///
+    /// New input settings are saved for the device; without them the device's
+    /// saved settings are used.
+    pub fn init_recording_session(
+        &mut self,
+        device_name: String,
+        input_settings: Option<InputSettings>,
+    ) -> Result<()> {
         info!(
             "Initializing recording session with device: {}",
             device_name
         );
+        let input_settings = match input_settings {
+            Some(settings) => {
+                self.device_settings
+                    .set(&device_name, settings.clone())
+                    .map_err(RecorderError::AudioError)?;
+                settings
+            }
+            None => self.device_settings.get(&device_name),
+        };
+        self.selected_device = Some(device_name.clone());
         self.with_thread(|tx, rx| {
-            tx.send(AudioCommand::InitRecordingSession(device_name))?;
+            tx.send(AudioCommand::InitRecordingSession(
+                device_name,
+                input_settings,
+            ))?;
             let response = rx.recv()?;
             Self::handle_response(response, |_| (), "init_recording_session", None)
         })
@@ -193,14 +276,52 @@ impl AudioManager {
         })
     }
 
-    /// Start recording audio
+    /// Get the current idle session policy
+    pub fn get_session_policy(&self) -> SessionPolicy {
+        self.session_policy.get().clone()
+    }
+
+    /// Change when an idle session releases the device and save the choice
+    pub fn set_session_policy(&mut self, policy: SessionPolicy) -> Result<()> {
+        self.session_policy
+            .set(policy.clone())
+            .map_err(RecorderError::AudioError)?;
+        self.with_thread(|tx, rx| {
+            tx.send(AudioCommand::SetSessionPolicy(policy))?;
+            let response = rx.recv()?;
+            Self::handle_response(response, |_| (), "set_session_policy", None)
+        })
+    }
+
+    /// Open `device`, or else the last used device, if the session was never opened,
+    /// was released while idle, or was left failed by a fatal stream error
+    pub fn ensure_session(&mut self, device: Option<&str>) -> Result<()> {
+        let state = self.get_recorder_state()?;
+        if state != "IDLE" && state != "FAILED" {
+            return Ok(());
+        }
+        let device_name = device
+            .map(str::to_string)
+            .or_else(|| self.selected_device.clone())
+            .unwrap_or_else(|| "default".to_string());
+        info!("No open session, opening '{}' on demand", device_name);
+        self.init_recording_session(device_name, None)
+    }
+
+    /// Start recording audio, opening the device first if needed
     pub fn start_recording(&mut self) -> Result<()> {
         info!("Starting recording");
-        self.with_thread(|tx, rx| {
+        if let Err(e) = self.ensure_session(None) {
+            return self.play_error_cue_on_failure(Err(e));
+        }
+        // Let the cue finish before capture starts so the first words are recorded
+        self.cues.play_and_wait(SoundCue::Start);
+        let result = self.with_thread(|tx, rx| {
             tx.send(AudioCommand::StartRecording)?;
             let response = rx.recv()?;
             Self::handle_response(response, |_| (), "start_recording", Some(true))
-        })
+        });
+        self.play_error_cue_on_failure(result)
     }
 
     /// Helper method to handle audio data responses
@@ -233,9 +354,8 @@ impl AudioManager {
         }
     }
 
-    /// Stop recording and return the recorded audio data with metadata
-    pub fn stop_recording(&mut self) -> Result<super::AudioRecording> {
-        info!("Stopping recording");
+    /// Stop the stream and collect the recorded audio without playing a cue
+    fn stop_capture(&mut self) -> Result<super::AudioRecording> {
         self.with_thread(|tx, rx| {
             tx.send(AudioCommand::StopRecording)?;
             let response = rx.recv()?;
@@ -243,11 +363,51 @@ impl AudioManager {
         })
     }
 
+    /// Stop recording and return the recorded audio data with metadata
+    pub fn stop_recording(&mut self) -> Result<super::AudioRecording> {
+        info!("Stopping recording");
+        let result = self.stop_capture();
+        if result.is_ok() {
+            // The stream is paused by now, so the cue cannot leak into the recording
+            self.cues.play(SoundCue::Stop);
+        }
+        self.play_error_cue_on_failure(result)
+    }
+
     /// Cancel the current recording
     pub fn cancel_recording(&mut self) -> Result<()> {
         info!("Canceling recording");
-        // Reuse stop_recording and discard the audio data
-        self.stop_recording().map(|_| ())
+        // Reuse the stop path and discard the audio data
+        let result = self.stop_capture().map(|_| ());
+        if result.is_ok() {
+            self.cues.play(SoundCue::Cancel);
+        }
+        self.play_error_cue_on_failure(result)
+    }
+
+    /// Play the error cue if an operation failed, passing the result through
+    fn play_error_cue_on_failure<T>(&mut self, result: Result<T>) -> Result<T> {
+        if result.is_err() {
+            self.cues.play(SoundCue::Error);
+        }
+        result
+    }
+
+    /// Get the current sound cue settings
+    pub fn get_sound_cue_settings(&self) -> SoundCueSettings {
+        self.cues.settings().clone()
+    }
+
+    /// Replace the sound cue settings
+    pub fn set_sound_cue_settings(&mut self, settings: SoundCueSettings) -> Result<()> {
+        self.cues
+            .set_settings(settings)
+            .map_err(RecorderError::AudioError)
+    }
+
+    /// Play a cue once regardless of whether cues are enabled
+    pub fn preview_sound_cue(&mut self, cue: SoundCue) {
+        self.cues.play_unchecked(cue);
     }
 
     /// Close the audio thread
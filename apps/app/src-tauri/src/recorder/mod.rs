@@ -1,13 +1,22 @@
 pub mod commands;
+pub mod cues;
 pub mod error;
+pub mod input;
 pub mod manager;
 pub mod thread;
+pub mod wav;
 
 pub use commands::{
-    cancel_recording, close_recording_session, enumerate_recording_devices, get_recorder_state,
-    init_recording_session, start_recording, stop_recording, AppData,
+    cancel_recording, close_recording_session, enumerate_recording_devices,
+    get_device_input_settings, get_recorder_state, get_session_policy, get_sound_cue_settings,
+    init_recording_session, preview_sound_cue, set_session_policy, set_sound_cue_settings,
+    start_recording, stop_recording, AppData,
 };
 
+pub use cues::{CueSource, SoundCue, SoundCueSettings};
+pub use input::InputSettings;
 pub use error::RecorderError;
 pub use manager::{AudioManager, DeviceInfo};
-pub use thread::{AudioCommand, AudioRecording, AudioResponse};
+pub use thread::{
+    AudioCommand, AudioRecording, AudioResponse, SessionPolicy, StreamErrorEvent, StreamErrorKind,
+};
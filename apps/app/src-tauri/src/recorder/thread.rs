@@ -1,19 +1,26 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{SampleFormat, Stream};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::sync::{
-    mpsc::{self, SendError},
+    mpsc::{self, RecvTimeoutError, SendError},
     Arc,
 };
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
+use super::input::{InputProcessing, InputSettings};
+use crate::store::Validate;
+
 /// Pre-allocate buffer for ~2 minutes at 16kHz (standard for voice)
 const INITIAL_BUFFER_CAPACITY: usize = 16000 * 120;
 
 /// Preferred sample rate for voice recording
 const VOICE_SAMPLE_RATE: u32 = 16000;
 
+/// How often the audio thread checks for stream errors while a session is open
+const STREAM_ERROR_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// Commands that can be sent to the audio thread
 #[derive(Debug)]
 pub enum AudioCommand {
@@ -23,14 +30,103 @@ pub enum AudioCommand {
     CloseThread,
     /// List available recording devices
     EnumerateRecordingDevices,
-    /// Initialize a recording session with the specified device
-    InitRecordingSession(String),
+    /// Initialize a recording session with the specified device and input settings
+    InitRecordingSession(String, InputSettings),
     /// Close the current recording session
     CloseRecordingSession,
     /// Start recording audio
     StartRecording,
     /// Stop recording and return the recorded audio
     StopRecording,
+    /// Change when an idle session releases the device
+    SetSessionPolicy(SessionPolicy),
+}
+
+/// Category of an error reported by the input stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamErrorKind {
+    /// Samples were dropped because the buffer wasn't drained in time
+    Overrun,
+    /// The device was unplugged or otherwise invalidated
+    DeviceNotAvailable,
+    /// Any other error from the audio backend
+    Backend,
+}
+
+/// Stream error forwarded from cpal to the app - matches TypeScript interface
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamErrorEvent {
+    pub kind: StreamErrorKind,
+    pub message: String,
+    /// Whether the stream can no longer deliver audio
+    pub fatal: bool,
+}
+
+impl From<cpal::StreamError> for StreamErrorEvent {
+    fn from(err: cpal::StreamError) -> Self {
+        let message = err.to_string();
+        let kind = match err {
+            cpal::StreamError::DeviceNotAvailable => StreamErrorKind::DeviceNotAvailable,
+            cpal::StreamError::BackendSpecific { .. } => {
+                let lower = message.to_lowercase();
+                if lower.contains("overrun") || lower.contains("xrun") {
+                    StreamErrorKind::Overrun
+                } else {
+                    StreamErrorKind::Backend
+                }
+            }
+        };
+        Self {
+            kind,
+            message,
+            fatal: kind == StreamErrorKind::DeviceNotAvailable,
+        }
+    }
+}
+
+/// Callback invoked on the audio thread for every stream error
+pub type StreamErrorHandler = Arc<dyn Fn(&StreamErrorEvent) + Send + Sync>;
+
+/// Shortest idle timeout, so a session opened for a starting recording isn't
+/// released again before the recording begins
+const MIN_IDLE_TIMEOUT_SECS: u64 = 5;
+
+/// When the audio thread releases an open but idle recording session
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionPolicy {
+    /// Keep the device open between recordings for the lowest start latency,
+    /// at the cost of the microphone indicator staying on
+    pub keep_warm: bool,
+    /// Seconds without activity before an idle session is closed, 0 for never.
+    /// Used when `keep_warm` is off.
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        // Release the microphone a minute after the last recording
+        Self {
+            keep_warm: false,
+            idle_timeout_secs: 60,
+        }
+    }
+}
+
+impl Validate for SessionPolicy {}
+
+impl SessionPolicy {
+    /// How long an idle session stays open, or `None` to keep it open
+    fn idle_timeout(&self) -> Option<Duration> {
+        if self.keep_warm || self.idle_timeout_secs == 0 {
+            return None;
+        }
+        Some(Duration::from_secs(
+            self.idle_timeout_secs.max(MIN_IDLE_TIMEOUT_SECS),
+        ))
+    }
 }
 
 /// Audio recording data with metadata - matches TypeScript interface
@@ -41,6 +137,10 @@ pub struct AudioRecording {
     pub sample_rate: u32,
     pub channels: u16,
     pub duration_seconds: f32,
+    /// Number of stream errors reported while recording
+    pub stream_errors: u32,
+    /// Message of the fatal error that ended the recording early, if any
+    pub failure: Option<String>,
 }
 
 /// Responses from the audio thread
@@ -63,6 +163,9 @@ struct RecordingSession {
     audio_buffer: Arc<Mutex<Vec<f32>>>,
     sample_rate: u32,
     channels: u16,
+    stream_errors: u32,
+    /// Set when a fatal stream error stopped the capture
+    failure: Option<String>,
 }
 
 impl Drop for RecordingSession {
@@ -77,19 +180,95 @@ impl Drop for RecordingSession {
     }
 }
 
-/// Spawns a new audio thread and returns a channel for sending commands to it
+/// Spawns a new audio thread and returns a channel for sending commands to it.
+///
+/// While `capture_gate` is non-zero a sound cue is playing and captured
+/// samples are replaced with silence. Stream errors arrive on a channel of
+/// their own and are passed to `on_stream_error`. The thread exits once every
+/// command sender has been dropped.
 pub fn spawn_audio_thread(
     response_tx: mpsc::Sender<AudioResponse>,
+    capture_gate: Arc<AtomicUsize>,
+    on_stream_error: Option<StreamErrorHandler>,
 ) -> Result<mpsc::Sender<AudioCommand>, SendError<AudioCommand>> {
     let (tx, rx) = mpsc::channel();
+    let (stream_error_tx, stream_error_rx) = mpsc::channel::<StreamErrorEvent>();
 
     std::thread::Builder::new()
         .name("audio-recorder".to_string())
         .spawn(move || -> Result<(), SendError<AudioResponse>> {
             let host = cpal::default_host();
             let mut current_session: Option<RecordingSession> = None;
+            let mut policy = SessionPolicy::default();
+            let mut last_activity = Instant::now();
+
+            loop {
+                for event in stream_error_rx.try_iter() {
+                    if let Some(session) = current_session.as_mut() {
+                        session.stream_errors += 1;
+
+                        if event.fatal && session.failure.is_none() {
+                            // Stop capturing but keep the buffer so the audio so far can be collected
+                            error!("Fatal stream error, recording failed: {}", event.message);
+                            session.is_recording.store(false, Ordering::Release);
+                            if let Err(e) = session.stream.pause() {
+                                debug!("Error pausing failed stream: {}", e);
+                            }
+                            session.failure = Some(event.message.clone());
+                        }
+                    }
+
+                    if let Some(handler) = &on_stream_error {
+                        handler(&event);
+                    }
+                }
+
+                // An open session that is not recording is released once it has been idle too long
+                let idle_deadline = match (&current_session, policy.idle_timeout()) {
+                    (Some(session), Some(timeout))
+                        if session.failure.is_none()
+                            && !session.is_recording.load(Ordering::Acquire) =>
+                    {
+                        Some(last_activity + timeout)
+                    }
+                    _ => None,
+                };
+
+                // While a session is open, wake up regularly to handle its stream errors
+                let cmd = if current_session.is_some() {
+                    let wait = idle_deadline.map_or(STREAM_ERROR_POLL_INTERVAL, |deadline| {
+                        deadline
+                            .saturating_duration_since(Instant::now())
+                            .min(STREAM_ERROR_POLL_INTERVAL)
+                    });
+                    match rx.recv_timeout(wait) {
+                        Ok(cmd) => cmd,
+                        Err(RecvTimeoutError::Timeout) => {
+                            if idle_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                                info!("Closing idle recording session to release the device");
+                                current_session = None;
+                            }
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                } else {
+                    match rx.recv() {
+                        Ok(cmd) => cmd,
+                        Err(_) => break,
+                    }
+                };
+
+                // State polling and device listing don't count as using the session
+                if matches!(
+                    cmd,
+                    AudioCommand::InitRecordingSession(..)
+                        | AudioCommand::StartRecording
+                        | AudioCommand::StopRecording
+                ) {
+                    last_activity = Instant::now();
+                }
 
-            while let Ok(cmd) = rx.recv() {
                 match cmd {
                     AudioCommand::EnumerateRecordingDevices => {
                         debug!("Audio thread: Enumerating recording devices");
@@ -105,7 +284,7 @@ pub fn spawn_audio_thread(
                         response_tx.send(AudioResponse::RecordingDeviceList(devices))?;
                     }
 
-                    AudioCommand::InitRecordingSession(device_name) => {
+                    AudioCommand::InitRecordingSession(device_name, input_settings) => {
                         info!(
                             "Audio thread: Initializing recording session with device: {}",
                             device_name
@@ -132,7 +311,10 @@ pub fn spawn_audio_thread(
                         };
 
                         // Get an optimal configuration for voice recording
-                        let config = match get_optimal_config(&device) {
+                        let config = match get_optimal_config(
+                            &device,
+                            input_settings.required_channels(),
+                        ) {
                             Ok(config) => config,
                             Err(e) => {
                                 error!("Failed to get device config for '{}': {}", device_name, e);
@@ -147,6 +329,7 @@ pub fn spawn_audio_thread(
                         let sample_format = config.sample_format();
                         let sample_rate = config.sample_rate().0;
                         let channels = config.channels();
+                        let processing = InputProcessing::new(&input_settings, channels);
 
                         // Create StreamConfig directly instead of converting
                         let stream_config = cpal::StreamConfig {
@@ -167,24 +350,27 @@ pub fn spawn_audio_thread(
                                 &stream_config,
                                 is_recording.clone(),
                                 audio_buffer.clone(),
-                                sample_rate,
-                                channels,
+                                capture_gate.clone(),
+                                processing.clone(),
+                                stream_error_tx.clone(),
                             ),
                             SampleFormat::I16 => build_stream_i16(
                                 &device,
                                 &stream_config,
                                 is_recording.clone(),
                                 audio_buffer.clone(),
-                                sample_rate,
-                                channels,
+                                capture_gate.clone(),
+                                processing.clone(),
+                                stream_error_tx.clone(),
                             ),
                             SampleFormat::U16 => build_stream_u16(
                                 &device,
                                 &stream_config,
                                 is_recording.clone(),
                                 audio_buffer.clone(),
-                                sample_rate,
-                                channels,
+                                capture_gate.clone(),
+                                processing.clone(),
+                                stream_error_tx.clone(),
                             ),
                             _ => {
                                 let err_msg = "Unsupported sample format".to_string();
@@ -204,17 +390,21 @@ pub fn spawn_audio_thread(
                             }
                         };
 
+                        // Channel selection may mix the device channels down to mono
+                        let channels = processing.output_channels();
                         current_session = Some(RecordingSession {
                             stream,
                             is_recording,
                             audio_buffer,
                             sample_rate,
                             channels,
+                            stream_errors: 0,
+                            failure: None,
                         });
 
                         info!(
-                            "Recording session initialized successfully: {}Hz, {} channels",
-                            sample_rate, channels
+                            "Recording session initialized successfully: {}Hz, {} channels, {:+.1} dB gain",
+                            sample_rate, channels, input_settings.gain_db
                         );
                         response_tx.send(AudioResponse::Success(format!(
                             "Recording session initialized: {}Hz, {} channels",
@@ -225,7 +415,9 @@ pub fn spawn_audio_thread(
                     AudioCommand::GetRecorderState => {
                         debug!("Audio thread: Getting recorder state");
                         let state = if let Some(session) = &current_session {
-                            if session.is_recording.load(Ordering::Acquire) {
+                            if session.failure.is_some() {
+                                "FAILED"
+                            } else if session.is_recording.load(Ordering::Acquire) {
                                 "RECORDING"
                             } else {
                                 "SESSION"
@@ -240,7 +432,15 @@ pub fn spawn_audio_thread(
 
                     AudioCommand::StartRecording => {
                         info!("Audio thread: Starting recording");
-                        if let Some(session) = &current_session {
+                        if let Some(session) = current_session.as_mut() {
+                            if let Some(failure) = &session.failure {
+                                let err_msg = format!("Recording session failed: {}", failure);
+                                error!("{}", err_msg);
+                                response_tx.send(AudioResponse::Error(err_msg))?;
+                                continue;
+                            }
+                            session.stream_errors = 0;
+
                             // Clear any existing data when starting a new recording
                             if let Ok(mut buffer) = session.audio_buffer.lock() {
                                 buffer.clear();
@@ -308,8 +508,16 @@ pub fn spawn_audio_thread(
                                 sample_rate: session.sample_rate,
                                 channels: session.channels,
                                 duration_seconds: duration_secs,
+                                stream_errors: session.stream_errors,
+                                failure: session.failure.clone(),
                             };
 
+                            // A failed stream can't be restarted, so drop the session
+                            // once its audio has been handed over
+                            if session.failure.is_some() {
+                                current_session = None;
+                            }
+
                             response_tx.send(AudioResponse::AudioData(audio_recording))?;
                         } else {
                             error!("Cannot stop recording: no active session");
@@ -335,6 +543,16 @@ pub fn spawn_audio_thread(
                         }
                     }
 
+                    AudioCommand::SetSessionPolicy(new_policy) => {
+                        info!(
+                            "Audio thread: Session policy set (keep warm: {}, idle timeout: {}s)",
+                            new_policy.keep_warm, new_policy.idle_timeout_secs
+                        );
+                        policy = new_policy;
+                        response_tx
+                            .send(AudioResponse::Success("Session policy updated".to_string()))?;
+                    }
+
                     AudioCommand::CloseThread => {
                         info!("Audio thread: Closing thread");
                         // Clean up any active session
@@ -363,24 +581,20 @@ fn build_stream_f32(
     config: &cpal::StreamConfig,
     is_recording: Arc<AtomicBool>,
     audio_buffer: Arc<Mutex<Vec<f32>>>,
-    _sample_rate: u32,
-    _channels: u16,
+    capture_gate: Arc<AtomicUsize>,
+    processing: InputProcessing,
+    error_tx: mpsc::Sender<StreamErrorEvent>,
 ) -> Result<Stream, cpal::BuildStreamError> {
-    let err_fn = |err| error!("Error in audio stream: {}", err);
+    let err_fn = stream_error_callback(error_tx);
 
     device.build_input_stream(
         config,
         move |data: &[f32], _: &_| {
             if is_recording.load(Ordering::Acquire) {
                 if let Ok(mut buffer) = audio_buffer.lock() {
-                    // Reserve space for new samples to avoid frequent reallocations
-                    let new_samples = data.len();
-                    if buffer.capacity() < buffer.len() + new_samples {
-                        buffer.reserve(new_samples);
-                    }
-
-                    // Directly extend with f32 data
-                    buffer.extend_from_slice(data);
+                    // While a sound cue is playing, keep the timeline but drop the audio
+                    let muted = capture_gate.load(Ordering::Acquire) > 0;
+                    processing.extend(&mut buffer, data, |s| s, muted);
                 }
             }
         },
@@ -395,25 +609,21 @@ fn build_stream_i16(
     config: &cpal::StreamConfig,
     is_recording: Arc<AtomicBool>,
     audio_buffer: Arc<Mutex<Vec<f32>>>,
-    _sample_rate: u32,
-    _channels: u16,
+    capture_gate: Arc<AtomicUsize>,
+    processing: InputProcessing,
+    error_tx: mpsc::Sender<StreamErrorEvent>,
 ) -> Result<Stream, cpal::BuildStreamError> {
     use cpal::Sample; // Bring Sample trait into scope
-    let err_fn = |err| error!("Error in audio stream: {}", err);
+    let err_fn = stream_error_callback(error_tx);
 
     device.build_input_stream(
         config,
         move |data: &[i16], _: &_| {
             if is_recording.load(Ordering::Acquire) {
                 if let Ok(mut buffer) = audio_buffer.lock() {
-                    // Reserve space for new samples to avoid frequent reallocations
-                    let new_samples = data.len();
-                    if buffer.capacity() < buffer.len() + new_samples {
-                        buffer.reserve(new_samples);
-                    }
-
-                    // Convert i16 to f32 and store
-                    buffer.extend(data.iter().map(|&s| f32::from_sample(s)));
+                    // While a sound cue is playing, keep the timeline but drop the audio
+                    let muted = capture_gate.load(Ordering::Acquire) > 0;
+                    processing.extend(&mut buffer, data, f32::from_sample, muted);
                 }
             }
         },
@@ -428,25 +638,21 @@ fn build_stream_u16(
     config: &cpal::StreamConfig,
     is_recording: Arc<AtomicBool>,
     audio_buffer: Arc<Mutex<Vec<f32>>>,
-    _sample_rate: u32,
-    _channels: u16,
+    capture_gate: Arc<AtomicUsize>,
+    processing: InputProcessing,
+    error_tx: mpsc::Sender<StreamErrorEvent>,
 ) -> Result<Stream, cpal::BuildStreamError> {
     use cpal::Sample; // Bring Sample trait into scope
-    let err_fn = |err| error!("Error in audio stream: {}", err);
+    let err_fn = stream_error_callback(error_tx);
 
     device.build_input_stream(
         config,
         move |data: &[u16], _: &_| {
             if is_recording.load(Ordering::Acquire) {
                 if let Ok(mut buffer) = audio_buffer.lock() {
-                    // Reserve space for new samples to avoid frequent reallocations
-                    let new_samples = data.len();
-                    if buffer.capacity() < buffer.len() + new_samples {
-                        buffer.reserve(new_samples);
-                    }
-
-                    // Convert u16 to f32 and store
-                    buffer.extend(data.iter().map(|&s| f32::from_sample(s)));
+                    // While a sound cue is playing, keep the timeline but drop the audio
+                    let muted = capture_gate.load(Ordering::Acquire) > 0;
+                    processing.extend(&mut buffer, data, f32::from_sample, muted);
                 }
             }
         },
@@ -455,6 +661,18 @@ fn build_stream_u16(
     )
 }
 
+/// Build the error callback for an input stream, forwarding errors to the audio thread
+fn stream_error_callback(
+    error_tx: mpsc::Sender<StreamErrorEvent>,
+) -> impl FnMut(cpal::StreamError) + Send + 'static {
+    move |err| {
+        error!("Error in audio stream: {}", err);
+        if let Err(e) = error_tx.send(err.into()) {
+            debug!("Audio thread gone, dropping stream error: {}", e);
+        }
+    }
+}
+
 /// Find a recording device by name
 fn find_device(host: &cpal::Host, device_name: &str) -> Result<cpal::Device, String> {
     // If "default" is requested, return default device
@@ -497,8 +715,14 @@ fn find_device(host: &cpal::Host, device_name: &str) -> Result<cpal::Device, Str
     ))
 }
 
-/// Get an optimal audio configuration for voice recording
-fn get_optimal_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig, String> {
+/// Get an optimal audio configuration for voice recording.
+///
+/// `min_channels` is the number of device channels the input channel selection
+/// needs; mono is only preferred when a single channel is enough.
+fn get_optimal_config(
+    device: &cpal::Device,
+    min_channels: u16,
+) -> Result<cpal::SupportedStreamConfig, String> {
     let supported_configs = device
         .supported_input_configs()
         .map_err(|e| e.to_string())?
@@ -517,7 +741,10 @@ fn get_optimal_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConf
             let channels = config.channels();
 
             // Prefer mono for voice recording
-            channels == 1 && min_rate <= VOICE_SAMPLE_RATE && max_rate >= VOICE_SAMPLE_RATE
+            min_channels <= 1
+                && channels == 1
+                && min_rate <= VOICE_SAMPLE_RATE
+                && max_rate >= VOICE_SAMPLE_RATE
         })
         .map(|range| range.with_sample_rate(cpal::SampleRate(VOICE_SAMPLE_RATE)));
 
@@ -531,14 +758,16 @@ fn get_optimal_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConf
         return Ok(config);
     }
 
-    // Next, try to find any configuration that supports voice sample rate
+    // Next, try to find any configuration with enough channels that supports voice sample rate
     let any_voice_rate = supported_configs
         .iter()
         .find(|config| {
             let min_rate = config.min_sample_rate().0;
             let max_rate = config.max_sample_rate().0;
 
-            min_rate <= VOICE_SAMPLE_RATE && max_rate >= VOICE_SAMPLE_RATE
+            config.channels() >= min_channels
+                && min_rate <= VOICE_SAMPLE_RATE
+                && max_rate >= VOICE_SAMPLE_RATE
         })
         .map(|range| range.with_sample_rate(cpal::SampleRate(VOICE_SAMPLE_RATE)));
 
@@ -552,6 +781,23 @@ fn get_optimal_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConf
         return Ok(config);
     }
 
+    // Then take the widest configuration with enough channels at its maximum rate
+    let enough_channels = supported_configs
+        .iter()
+        .filter(|config| config.channels() >= min_channels)
+        .max_by_key(|config| config.channels())
+        .map(|range| range.with_max_sample_rate());
+
+    if let Some(config) = enough_channels.filter(|_| min_channels > 1) {
+        info!(
+            "Using multi-channel config: {} Hz, {} channels, {:?} format",
+            config.sample_rate().0,
+            config.channels(),
+            config.sample_format()
+        );
+        return Ok(config);
+    }
+
     // Finally, fall back to default configuration
     device
         .default_input_config()
@@ -566,3 +812,35 @@ fn get_optimal_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConf
             config
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_timeout() {
+        let policy = |keep_warm, idle_timeout_secs| SessionPolicy {
+            keep_warm,
+            idle_timeout_secs,
+        };
+        assert_eq!(
+            SessionPolicy::default().idle_timeout(),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(policy(true, 30).idle_timeout(), None);
+        assert_eq!(policy(false, 0).idle_timeout(), None);
+        assert_eq!(policy(false, 1).idle_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(policy(false, 60).idle_timeout(), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn thread_exits_when_command_sender_is_dropped() {
+        let (response_tx, response_rx) = mpsc::channel();
+        let command_tx = spawn_audio_thread(response_tx, Arc::new(AtomicUsize::new(0)), None)
+            .expect("spawn audio thread");
+
+        drop(command_tx);
+        // The thread drops its response sender on exit
+        assert!(response_rx.recv_timeout(Duration::from_secs(5)).is_err());
+    }
+}
@@ -0,0 +1,24 @@
+use crate::recorder::AudioRecording;
+use std::io::Cursor;
+
+/// Encode a recording as a 16-bit PCM WAV file, the format transcription APIs expect
+pub fn encode_wav(recording: &AudioRecording) -> Result<Vec<u8>, String> {
+    let spec = hound::WavSpec {
+        channels: recording.channels.max(1),
+        sample_rate: recording.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::with_capacity(44 + recording.audio_data.len() * 2));
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).map_err(|e| e.to_string())?;
+        for &sample in &recording.audio_data {
+            let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            writer.write_sample(sample).map_err(|e| e.to_string())?;
+        }
+        writer.finalize().map_err(|e| e.to_string())?;
+    }
+
+    Ok(cursor.into_inner())
+}
@@ -0,0 +1,348 @@
+//! Fit dictated text to the text around the cursor: leading and trailing spaces,
+//! first-letter case and trailing punctuation.
+
+use crate::context::VoiceContext;
+
+/// Characters after which the next word starts without a space
+const OPENERS: &[char] = &['(', '[', '{', '“', '‘', '«', '¿', '¡'];
+/// Characters that attach to the word before them
+const CLOSERS: &[char] = &[
+    '.', ',', ';', ':', '!', '?', ')', ']', '}', '”', '’', '»', '%', '…',
+];
+/// Characters that may follow a sentence end, as in `He left.)` or `"Stop!"`
+const TRAILING_CLOSERS: &[char] = &['"', '\'', ')', ']', '}', '”', '’', '»'];
+/// Words ending in a period that don't end a sentence
+const ABBREVIATIONS: &[&str] = &["e.g.", "i.e.", "vs.", "mr.", "mrs.", "ms.", "dr.", "cf."];
+
+/// What the text before the cursor says about the first letter of the insertion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Boundary {
+    /// Start of the field, a line or a sentence
+    SentenceStart,
+    /// Inside a sentence
+    MidSentence,
+    /// After a colon or similar, where either case is fine
+    Unknown,
+}
+
+fn boundary(before: &str) -> Boundary {
+    let trimmed = before.trim_end_matches([' ', '\t']);
+    if trimmed.is_empty() || trimmed.ends_with('\n') || trimmed.ends_with('\r') {
+        return Boundary::SentenceStart;
+    }
+    let last_word = trimmed
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if ABBREVIATIONS.contains(&last_word.as_str()) {
+        return Boundary::MidSentence;
+    }
+    // Quotations may start with either case
+    let opening_quote = match trimmed.chars().last() {
+        Some('“' | '‘' | '«') => true,
+        Some('"' | '\'') => opens_quote(trimmed),
+        _ => false,
+    };
+    if opening_quote {
+        return Boundary::Unknown;
+    }
+    match trimmed.trim_end_matches(TRAILING_CLOSERS).chars().last() {
+        Some('.' | '!' | '?' | '…') | None => Boundary::SentenceStart,
+        Some(':' | ';') => Boundary::Unknown,
+        Some(_) => Boundary::MidSentence,
+    }
+}
+
+/// Whether a straight quote at the end of `before` opens a quotation
+fn opens_quote(before: &str) -> bool {
+    let mut chars = before.chars().rev();
+    chars.next();
+    chars
+        .next()
+        .is_none_or(|c| c.is_whitespace() || OPENERS.contains(&c))
+}
+
+fn needs_leading_space(before: &str, text: &str) -> bool {
+    let Some(last) = before.chars().last() else {
+        return false;
+    };
+    let Some(first) = text.chars().next() else {
+        return false;
+    };
+    if last.is_whitespace() || OPENERS.contains(&last) {
+        return false;
+    }
+    if (last == '"' || last == '\'') && opens_quote(before) {
+        return false;
+    }
+    !(first.is_whitespace() || CLOSERS.contains(&first))
+}
+
+fn needs_trailing_space(text: &str, after: &str) -> bool {
+    let Some(first) = after.chars().next() else {
+        return false;
+    };
+    let ends_in_space = text.chars().last().is_some_and(char::is_whitespace);
+    !ends_in_space && (first.is_alphanumeric() || OPENERS.contains(&first))
+}
+
+/// Byte range of the first word, if the text starts with one after any opening quotes
+fn first_word(text: &str) -> Option<(usize, usize)> {
+    let start = text
+        .char_indices()
+        .find(|(_, c)| !OPENERS.contains(c) && *c != '"' && *c != '\'')
+        .map(|(i, _)| i)?;
+    if !text[start..].starts_with(char::is_alphabetic) {
+        return None;
+    }
+    let end = text[start..]
+        .find(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’' || c == '-'))
+        .map_or(text.len(), |i| start + i);
+    Some((start, end))
+}
+
+/// Words that keep their capital in the middle of a sentence
+fn keeps_capital(word: &str, keep_case: &[String]) -> bool {
+    word == "I"
+        || word.starts_with("I'")
+        || word.starts_with("I’")
+        || word.chars().filter(|c| c.is_uppercase()).count() > 1
+        || keep_case
+            .iter()
+            .any(|term| term.split_whitespace().next() == Some(word))
+}
+
+fn set_first_letter(text: &str, boundary: Boundary, keep_case: &[String]) -> String {
+    let Some((start, end)) = first_word(text) else {
+        return text.to_string();
+    };
+    let word = &text[start..end];
+    let mut chars = word.chars();
+    let Some(first) = chars.next() else {
+        return text.to_string();
+    };
+    let first: String = match boundary {
+        // Names like iPhone keep their lowercase start
+        Boundary::SentenceStart if chars.clone().any(char::is_uppercase) => {
+            return text.to_string()
+        }
+        Boundary::SentenceStart => first.to_uppercase().collect(),
+        Boundary::MidSentence if !keeps_capital(word, keep_case) => first.to_lowercase().collect(),
+        Boundary::MidSentence | Boundary::Unknown => return text.to_string(),
+    };
+    format!(
+        "{}{}{}{}",
+        &text[..start],
+        first,
+        chars.as_str(),
+        &text[end..]
+    )
+}
+
+/// Drop a closing period that would end the sentence the cursor is in the middle of,
+/// or one that would double up with punctuation already after the cursor
+fn fix_trailing_punctuation(text: &str, after: &str) -> String {
+    let next = after.trim_start_matches([' ', '\t']).chars().next();
+    if text.ends_with("...") || text.ends_with('…') {
+        return text.to_string();
+    }
+    match next {
+        Some(c) if c.is_lowercase() => text.strip_suffix('.').unwrap_or(text).to_string(),
+        Some('.' | ',' | ';' | ':' | '!' | '?') => {
+            text.trim_end_matches(['.', ',', ';', ':']).to_string()
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Fit `text` between `before` and `after`. Words in `keep_case` are never lowercased.
+pub fn fit_between(text: &str, before: &str, after: &str, keep_case: &[String]) -> String {
+    let text = text.trim_matches([' ', '\t']);
+    if text.is_empty() {
+        return String::new();
+    }
+
+    // A leading newline from a voice command already starts the line
+    let text = if text.starts_with('\n') {
+        text.to_string()
+    } else {
+        set_first_letter(text, boundary(before), keep_case)
+    };
+    let text = fix_trailing_punctuation(&text, after);
+
+    let mut fitted = String::with_capacity(text.len() + 2);
+    if needs_leading_space(before, &text) {
+        fitted.push(' ');
+    }
+    fitted.push_str(&text);
+    if needs_trailing_space(&text, after) {
+        fitted.push(' ');
+    }
+    fitted
+}
+
+/// Fit dictated text to the cursor position captured in the context
+pub fn fit_to_context(text: &str, context: &VoiceContext) -> String {
+    fit_between(
+        text,
+        &context.text_before_cursor,
+        &context.text_after_cursor,
+        &context.vocabulary,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fit(before: &str, text: &str, after: &str) -> String {
+        fit_between(text, before, after, &[])
+    }
+
+    #[test]
+    fn leading_space() {
+        let cases = [
+            ("", "Hello.", "", "Hello."),
+            ("Hello", "world", "", " world"),
+            ("Hello ", "world", "", "world"),
+            ("Hello\n", "World", "", "World"),
+            ("Hello.", "Next one.", "", " Next one."),
+            ("Details (", "See above", "", "see above"),
+            ("He said \"", "Stop", "", "Stop"),
+            ("\"Quoted\"", "and more", "", " and more"),
+            ("Hello", ", world", "", ", world"),
+            ("Hello", "?", "", "?"),
+            ("Hello", "  world  ", "", " world"),
+            ("50", "%", "", "%"),
+        ];
+        for (before, text, after, expected) in cases {
+            assert_eq!(
+                fit(before, text, after),
+                expected,
+                "{:?} | {:?}",
+                before,
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn trailing_space() {
+        let cases = [
+            ("", "Hello", "world", "Hello "),
+            ("", "Hello", " world", "Hello"),
+            ("", "Hello", ".", "Hello"),
+            ("", "Hello", "(aside)", "Hello "),
+            ("", "Hello", "\nNext", "Hello"),
+        ];
+        for (before, text, after, expected) in cases {
+            assert_eq!(
+                fit(before, text, after),
+                expected,
+                "{:?} | {:?}",
+                text,
+                after
+            );
+        }
+    }
+
+    #[test]
+    fn capitalization() {
+        let cases = [
+            ("", "hello there.", "", "Hello there."),
+            ("The end. ", "next sentence.", "", "Next sentence."),
+            ("Really? ", "yes.", "", "Yes."),
+            ("Wow! ", "okay", "", "Okay"),
+            ("\"Done.\" ", "then", "", "Then"),
+            ("First line\n", "second", "", "Second"),
+            ("I think ", "That works", "", "that works"),
+            ("I think", "That works", "", " that works"),
+            ("and then ", "I left", "", "I left"),
+            ("and then ", "I'm leaving", "", "I'm leaving"),
+            ("we use ", "API keys", "", "API keys"),
+            ("I bought an ", "iPhone", "", "iPhone"),
+            ("Note: ", "Bring water", "", "Bring water"),
+            ("Note: ", "bring water", "", "bring water"),
+            ("for example e.g. ", "Apples", "", "apples"),
+            ("ours vs. ", "Theirs", "", "theirs"),
+            ("He said \"", "Stop", "", "Stop"),
+            ("He said “", "stop", "", "stop"),
+            ("", "iPhone sales", "", "iPhone sales"),
+            ("and ", "\"Quoted\" words", "", "\"quoted\" words"),
+            ("The end. ", "42 apples", "", "42 apples"),
+            ("Über ", "Über", "", "über"),
+            ("", "élan", "", "Élan"),
+        ];
+        for (before, text, after, expected) in cases {
+            assert_eq!(
+                fit(before, text, after),
+                expected,
+                "{:?} | {:?}",
+                before,
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_dictionary_terms_capitalized() {
+        let keep = vec!["Kubernetes".to_string(), "Acme Corp".to_string()];
+        assert_eq!(
+            fit_between("Kubernetes cluster", "we run a ", "", &keep),
+            "Kubernetes cluster"
+        );
+        assert_eq!(fit_between("Acme deal", "the ", "", &keep), "Acme deal");
+        assert_eq!(fit_between("Cluster", "the ", "", &keep), "cluster");
+    }
+
+    #[test]
+    fn trailing_punctuation() {
+        let cases = [
+            ("", "Meet tomorrow.", "", "Meet tomorrow."),
+            ("Let's ", "meet tomorrow.", "and talk", "meet tomorrow "),
+            ("Let's ", "meet tomorrow.", " and talk", "meet tomorrow"),
+            ("", "Meet tomorrow.", " Then talk.", "Meet tomorrow."),
+            ("Let's ", "meet tomorrow.", ".", "meet tomorrow"),
+            ("Let's ", "meet tomorrow,", ", then", "meet tomorrow"),
+            ("Wait ", "really?", ".", "really?"),
+            ("Well ", "maybe...", " not", "maybe..."),
+            ("Well ", "maybe…", "not", "maybe… "),
+        ];
+        for (before, text, after, expected) in cases {
+            assert_eq!(
+                fit(before, text, after),
+                expected,
+                "{:?} | {:?}",
+                text,
+                after
+            );
+        }
+    }
+
+    #[test]
+    fn middle_of_a_word_pair() {
+        assert_eq!(fit("Send the", "Final report.", "today"), " final report ");
+        assert_eq!(fit("Send the ", "Final report.", " today"), "final report");
+    }
+
+    #[test]
+    fn keeps_leading_newline_from_commands() {
+        assert_eq!(fit("Done.", "\nNext paragraph", ""), "\nNext paragraph");
+    }
+
+    #[test]
+    fn empty_text_stays_empty() {
+        assert_eq!(fit("Hello", "   ", "world"), "");
+    }
+
+    #[test]
+    fn uses_context_fields() {
+        let context = VoiceContext {
+            text_before_cursor: "Thanks for".to_string(),
+            text_after_cursor: " yesterday.".to_string(),
+            ..VoiceContext::default()
+        };
+        assert_eq!(fit_to_context("The update.", &context), " the update");
+    }
+}
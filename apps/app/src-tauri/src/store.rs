@@ -0,0 +1,125 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+use tracing::{debug, warn};
+
+/// Settings that are checked before they are saved and after they are loaded
+pub trait Validate {
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// A value persisted as pretty JSON in one file in the app data directory.
+/// A missing file gives the default; an unreadable or invalid one is ignored with
+/// a warning. Stores that were never loaded keep their value in memory only.
+#[derive(Debug, Default)]
+pub struct JsonStore<T> {
+    path: Option<PathBuf>,
+    value: T,
+}
+
+impl<T> JsonStore<T>
+where
+    T: Serialize + DeserializeOwned + Default + Validate,
+{
+    /// Read the value saved at `path`, falling back to the default
+    pub fn load(path: PathBuf) -> Self {
+        let value = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<T>(&content) {
+                Ok(value) => match value.validate() {
+                    Ok(()) => value,
+                    Err(e) => {
+                        warn!("Ignoring invalid settings in {:?}: {}", path, e);
+                        T::default()
+                    }
+                },
+                Err(e) => {
+                    warn!("Ignoring invalid settings in {:?}: {}", path, e);
+                    T::default()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => T::default(),
+            Err(e) => {
+                warn!("Ignoring unreadable settings in {:?}: {}", path, e);
+                T::default()
+            }
+        };
+        Self {
+            path: Some(path),
+            value,
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Validate and replace the value, then write it to disk
+    pub fn set(&mut self, value: T) -> Result<(), String> {
+        value.validate()?;
+        self.value = value;
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            debug!("Store not loaded, keeping settings in memory");
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&self.value).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| format!("Failed to save {:?}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Limit {
+        max: u32,
+    }
+
+    impl Validate for Limit {
+        fn validate(&self) -> Result<(), String> {
+            if self.max > 10 {
+                return Err("max is too large".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn saves_and_reloads_valid_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("limit.json");
+
+        let mut store = JsonStore::<Limit>::load(path.clone());
+        assert_eq!(store.get(), &Limit::default());
+        store.set(Limit { max: 3 }).unwrap();
+        assert!(store.set(Limit { max: 11 }).is_err());
+
+        assert_eq!(JsonStore::<Limit>::load(path).get(), &Limit { max: 3 });
+    }
+
+    #[test]
+    fn falls_back_to_default_for_invalid_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("limit.json");
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert_eq!(
+            JsonStore::<Limit>::load(path.clone()).get(),
+            &Limit::default()
+        );
+
+        std::fs::write(&path, r#"{ "max": 50 }"#).unwrap();
+        assert_eq!(JsonStore::<Limit>::load(path).get(), &Limit::default());
+    }
+}
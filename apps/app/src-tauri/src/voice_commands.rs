@@ -0,0 +1,623 @@
+use crate::store::{JsonStore, Validate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tracing::{debug, info, warn};
+
+const VOICE_COMMANDS_FILE: &str = "voice_commands.json";
+/// Language names and ISO 639-3 codes that providers report, with their ISO 639-1 code
+const LANGUAGE_CODES: &[(&str, &str)] = &[
+    ("english", "en"),
+    ("eng", "en"),
+    ("german", "de"),
+    ("deutsch", "de"),
+    ("deu", "de"),
+    ("ger", "de"),
+    ("french", "fr"),
+    ("français", "fr"),
+    ("fra", "fr"),
+    ("fre", "fr"),
+    ("spanish", "es"),
+    ("español", "es"),
+    ("spa", "es"),
+    ("italian", "it"),
+    ("ita", "it"),
+    ("portuguese", "pt"),
+    ("por", "pt"),
+    ("dutch", "nl"),
+    ("nld", "nl"),
+    ("dut", "nl"),
+    ("polish", "pl"),
+    ("pol", "pl"),
+    ("russian", "ru"),
+    ("rus", "ru"),
+    ("japanese", "ja"),
+    ("jpn", "ja"),
+    ("chinese", "zh"),
+    ("zho", "zh"),
+    ("chi", "zh"),
+];
+
+/// Which neighbours a spoken symbol sticks to - matches TypeScript interface
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Attach {
+    /// Spaces on both sides, e.g. `&`
+    #[default]
+    None,
+    /// No space before, e.g. `,` or a closing quote
+    Left,
+    /// No space after, e.g. an opening quote
+    Right,
+    /// No spaces at all, e.g. newlines
+    Both,
+}
+
+impl Attach {
+    fn left(self) -> bool {
+        matches!(self, Attach::Left | Attach::Both)
+    }
+
+    fn right(self) -> bool {
+        matches!(self, Attach::Right | Attach::Both)
+    }
+}
+
+/// A spoken phrase that inserts punctuation or layout - matches TypeScript interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpokenSymbol {
+    pub phrase: String,
+    pub text: String,
+    #[serde(default)]
+    pub attach: Attach,
+}
+
+/// Commands for one language - matches TypeScript interface
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandGrammar {
+    #[serde(default)]
+    pub symbols: Vec<SpokenSymbol>,
+    /// Phrases that remove the words since the last punctuation mark
+    #[serde(default)]
+    pub scratch_that: Vec<String>,
+    /// Phrases that remove the current or previous sentence
+    #[serde(default)]
+    pub delete_last_sentence: Vec<String>,
+}
+
+/// Spoken command settings - matches TypeScript interface
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VoiceCommandSettings {
+    /// Off by default: command words like "period" or "comma" also occur in ordinary speech
+    pub enabled: bool,
+    /// Grammars by language code such as `en` or `de`, replacing the built-in ones
+    pub grammars: BTreeMap<String, CommandGrammar>,
+}
+
+impl VoiceCommandSettings {
+    /// Grammar for a language or locale such as `de-AT`, if there is one
+    pub fn grammar(&self, language: &str) -> Option<CommandGrammar> {
+        let language = primary_language(language);
+        self.grammars
+            .get(&language)
+            .cloned()
+            .or_else(|| builtin_grammar(&language))
+    }
+}
+
+impl Validate for VoiceCommandSettings {}
+
+/// `de` for `de`, `DE`, `de-AT`, `de_AT`, `deu` or `German`
+pub fn primary_language(language: &str) -> String {
+    let language = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    LANGUAGE_CODES
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map_or(language, |(_, code)| code.to_string())
+}
+
+fn symbols(entries: &[(&str, &str, Attach)]) -> Vec<SpokenSymbol> {
+    entries
+        .iter()
+        .map(|(phrase, text, attach)| SpokenSymbol {
+            phrase: phrase.to_string(),
+            text: text.to_string(),
+            attach: *attach,
+        })
+        .collect()
+}
+
+fn phrases(entries: &[&str]) -> Vec<String> {
+    entries.iter().map(|phrase| phrase.to_string()).collect()
+}
+
+/// The grammar shipped for a language, if there is one
+pub fn builtin_grammar(language: &str) -> Option<CommandGrammar> {
+    use Attach::{Both, Left, None, Right};
+    let grammar = match language {
+        "en" => CommandGrammar {
+            symbols: symbols(&[
+                ("period", ".", Left),
+                ("full stop", ".", Left),
+                ("comma", ",", Left),
+                ("question mark", "?", Left),
+                ("exclamation mark", "!", Left),
+                ("exclamation point", "!", Left),
+                ("colon", ":", Left),
+                ("semicolon", ";", Left),
+                ("ellipsis", "...", Left),
+                ("new line", "\n", Both),
+                ("new paragraph", "\n\n", Both),
+                ("open quote", "\"", Right),
+                ("close quote", "\"", Left),
+                ("end quote", "\"", Left),
+                ("open paren", "(", Right),
+                ("open parenthesis", "(", Right),
+                ("close paren", ")", Left),
+                ("close parenthesis", ")", Left),
+                ("hyphen", "-", Both),
+                ("dash", "-", None),
+                ("ampersand", "&", None),
+            ]),
+            scratch_that: phrases(&["scratch that", "strike that"]),
+            delete_last_sentence: phrases(&["delete last sentence"]),
+        },
+        "de" => CommandGrammar {
+            symbols: symbols(&[
+                ("punkt", ".", Left),
+                ("komma", ",", Left),
+                ("fragezeichen", "?", Left),
+                ("ausrufezeichen", "!", Left),
+                ("doppelpunkt", ":", Left),
+                ("semikolon", ";", Left),
+                ("neue zeile", "\n", Both),
+                ("neuer absatz", "\n\n", Both),
+                ("anführungszeichen auf", "\"", Right),
+                ("anführungszeichen zu", "\"", Left),
+                ("klammer auf", "(", Right),
+                ("klammer zu", ")", Left),
+                ("bindestrich", "-", Both),
+            ]),
+            scratch_that: phrases(&["streich das", "lösch das"]),
+            delete_last_sentence: phrases(&["letzten satz löschen", "lösche den letzten satz"]),
+        },
+        "fr" => CommandGrammar {
+            symbols: symbols(&[
+                ("point", ".", Left),
+                ("virgule", ",", Left),
+                ("point d'interrogation", "?", Left),
+                ("point d'exclamation", "!", Left),
+                ("deux points", ":", Left),
+                ("point-virgule", ";", Left),
+                ("point virgule", ";", Left),
+                ("à la ligne", "\n", Both),
+                ("nouvelle ligne", "\n", Both),
+                ("nouveau paragraphe", "\n\n", Both),
+                ("ouvrez les guillemets", "\"", Right),
+                ("fermez les guillemets", "\"", Left),
+                ("ouvrir la parenthèse", "(", Right),
+                ("fermer la parenthèse", ")", Left),
+                ("trait d'union", "-", Both),
+            ]),
+            scratch_that: phrases(&["annule ça", "efface ça"]),
+            delete_last_sentence: phrases(&["supprime la dernière phrase"]),
+        },
+        "es" => CommandGrammar {
+            symbols: symbols(&[
+                ("punto", ".", Left),
+                ("coma", ",", Left),
+                ("signo de interrogación", "?", Left),
+                ("signo de exclamación", "!", Left),
+                ("dos puntos", ":", Left),
+                ("punto y coma", ";", Left),
+                ("nueva línea", "\n", Both),
+                ("nuevo párrafo", "\n\n", Both),
+                ("abrir comillas", "\"", Right),
+                ("cerrar comillas", "\"", Left),
+                ("abrir paréntesis", "(", Right),
+                ("cerrar paréntesis", ")", Left),
+                ("guion", "-", Both),
+            ]),
+            scratch_that: phrases(&["borra eso", "tacha eso"]),
+            delete_last_sentence: phrases(&["borra la última oración", "borra la última frase"]),
+        },
+        _ => return Option::None,
+    };
+    Some(grammar)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command<'a> {
+    Symbol(&'a SpokenSymbol),
+    ScratchThat,
+    DeleteLastSentence,
+}
+
+/// A word or inserted symbol in the output
+#[derive(Debug)]
+struct Piece {
+    /// Whitespace the transcript had before it, kept so line breaks survive
+    separator: String,
+    text: String,
+    attach: Attach,
+    is_symbol: bool,
+}
+
+/// Compare spoken words without case or the punctuation transcribers add around them
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+fn ends_clause(piece: &Piece) -> bool {
+    piece.is_symbol || piece.text.ends_with([',', '.', '?', '!', ':', ';'])
+}
+
+fn ends_sentence(piece: &Piece) -> bool {
+    piece.text.contains('\n') || piece.text.trim_end().ends_with(['.', '?', '!'])
+}
+
+/// Pop pieces back to the last boundary. When the text already ends on one,
+/// that boundary and the words before it go instead.
+fn remove_last(pieces: &mut Vec<Piece>, is_boundary: fn(&Piece) -> bool) {
+    if pieces.last().is_some_and(is_boundary) {
+        pieces.pop();
+    }
+    while pieces.last().is_some_and(|piece| !is_boundary(piece)) {
+        pieces.pop();
+    }
+}
+
+fn join(pieces: &[Piece]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&Piece> = Option::None;
+    for piece in pieces {
+        if let Some(previous) = previous {
+            if !previous.attach.right() && !piece.attach.left() {
+                text.push_str(if piece.separator.is_empty() {
+                    " "
+                } else {
+                    &piece.separator
+                });
+            }
+        }
+        text.push_str(&piece.text);
+        previous = Some(piece);
+    }
+    text
+}
+
+/// Words of the text with the whitespace before each of them
+fn words_with_separators(text: &str) -> Vec<(&str, &str)> {
+    let mut words = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        let end = rest[start..]
+            .find(char::is_whitespace)
+            .map_or(rest.len(), |i| start + i);
+        words.push((&rest[..start], &rest[start..end]));
+        rest = &rest[end..];
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        Option::None => String::new(),
+    }
+}
+
+impl CommandGrammar {
+    /// All command phrases as normalized words, longest first
+    fn phrases(&self) -> Vec<(Vec<String>, Command<'_>)> {
+        let words = |phrase: &str| -> Vec<String> {
+            phrase
+                .split_whitespace()
+                .map(normalize)
+                .filter(|word| !word.is_empty())
+                .collect()
+        };
+        let mut phrases: Vec<(Vec<String>, Command)> = self
+            .symbols
+            .iter()
+            .map(|symbol| (words(&symbol.phrase), Command::Symbol(symbol)))
+            .chain(
+                self.scratch_that
+                    .iter()
+                    .map(|phrase| (words(phrase), Command::ScratchThat)),
+            )
+            .chain(
+                self.delete_last_sentence
+                    .iter()
+                    .map(|phrase| (words(phrase), Command::DeleteLastSentence)),
+            )
+            .filter(|(words, _)| !words.is_empty())
+            .collect();
+        phrases.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        phrases
+    }
+
+    /// Replace spoken commands in a transcript with the text they stand for
+    pub fn apply(&self, text: &str) -> String {
+        let phrases = self.phrases();
+        let (separators, words): (Vec<&str>, Vec<&str>) =
+            words_with_separators(text).into_iter().unzip();
+        let normalized: Vec<String> = words.iter().map(|word| normalize(word)).collect();
+        let mut pieces: Vec<Piece> = Vec::new();
+        let mut capitalize_next = false;
+
+        let mut i = 0;
+        while i < words.len() {
+            let command = phrases
+                .iter()
+                .find(|(phrase, _)| normalized.get(i..i + phrase.len()) == Some(phrase.as_slice()));
+            let Some((phrase, command)) = command else {
+                let word = if capitalize_next {
+                    capitalize(words[i])
+                } else {
+                    words[i].to_string()
+                };
+                pieces.push(Piece {
+                    separator: separators[i].to_string(),
+                    text: word,
+                    attach: Attach::None,
+                    is_symbol: false,
+                });
+                capitalize_next = false;
+                i += 1;
+                continue;
+            };
+
+            debug!("Voice command {:?} at word {}", command, i);
+            match command {
+                Command::Symbol(symbol) => {
+                    // Drop punctuation the transcriber already put where the command was said
+                    if symbol.attach.left() {
+                        if let Some(previous) = pieces.last_mut().filter(|p| !p.is_symbol) {
+                            let trimmed = previous
+                                .text
+                                .trim_end_matches([',', '.', '?', '!', ':', ';'])
+                                .len();
+                            previous.text.truncate(trimmed);
+                        }
+                    }
+                    let piece = Piece {
+                        separator: separators[i].to_string(),
+                        text: symbol.text.clone(),
+                        attach: symbol.attach,
+                        is_symbol: true,
+                    };
+                    capitalize_next = ends_sentence(&piece);
+                    pieces.push(piece);
+                }
+                Command::ScratchThat | Command::DeleteLastSentence => {
+                    let is_boundary = if *command == Command::ScratchThat {
+                        ends_clause
+                    } else {
+                        ends_sentence
+                    };
+                    remove_last(&mut pieces, is_boundary);
+                    capitalize_next = pieces
+                        .last()
+                        .is_some_and(|piece| piece.is_symbol && ends_sentence(piece));
+                }
+            }
+            i += phrase.len();
+        }
+
+        join(&pieces)
+    }
+}
+
+/// Voice command settings persisted as JSON in the app data directory
+#[derive(Debug, Default)]
+pub struct VoiceCommandStore {
+    store: JsonStore<VoiceCommandSettings>,
+}
+
+impl VoiceCommandStore {
+    /// Load saved settings from the given app data directory, falling back to defaults
+    pub fn load(app_dir: PathBuf) -> Self {
+        let store = JsonStore::<VoiceCommandSettings>::load(app_dir.join(VOICE_COMMANDS_FILE));
+        let settings = store.get();
+        info!(
+            "Voice commands {}, {} custom grammars",
+            if settings.enabled { "on" } else { "off" },
+            settings.grammars.len()
+        );
+        Self { store }
+    }
+
+    pub fn settings(&self) -> &VoiceCommandSettings {
+        self.store.get()
+    }
+
+    /// Replace the settings and write them to disk
+    pub fn set_settings(&mut self, settings: VoiceCommandSettings) -> Result<(), String> {
+        self.store.set(settings)
+    }
+}
+
+/// Apply the spoken commands for `language` using the app's settings. Text in
+/// languages without a grammar is returned unchanged.
+pub fn apply_for_app(app: &AppHandle, text: &str, language: &str) -> String {
+    let grammar = match app.try_state::<Mutex<VoiceCommandStore>>() {
+        Some(store) => match store.lock() {
+            Ok(store) if store.settings().enabled => store.settings().grammar(language),
+            Ok(_) => return text.to_string(),
+            Err(e) => {
+                warn!("Failed to lock voice command settings: {}", e);
+                return text.to_string();
+            }
+        },
+        None => return text.to_string(),
+    };
+    match grammar {
+        Some(grammar) => grammar.apply(text),
+        None => {
+            debug!("No voice commands for '{}'", language);
+            text.to_string()
+        }
+    }
+}
+
+/// Get spoken command settings
+#[tauri::command]
+pub async fn get_voice_command_settings(
+    store: State<'_, Mutex<VoiceCommandStore>>,
+) -> Result<VoiceCommandSettings, String> {
+    let store = store.lock().map_err(|e| e.to_string())?;
+    Ok(store.settings().clone())
+}
+
+/// Replace and save spoken command settings
+#[tauri::command]
+pub async fn set_voice_command_settings(
+    settings: VoiceCommandSettings,
+    store: State<'_, Mutex<VoiceCommandStore>>,
+) -> Result<(), String> {
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    store.set_settings(settings)
+}
+
+/// Get the grammar in effect for a language, as a starting point for customizing it.
+/// Languages without one get an empty grammar.
+#[tauri::command]
+pub async fn get_command_grammar(
+    language: String,
+    store: State<'_, Mutex<VoiceCommandStore>>,
+) -> Result<CommandGrammar, String> {
+    let store = store.lock().map_err(|e| e.to_string())?;
+    Ok(store.settings().grammar(&language).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english(text: &str) -> String {
+        builtin_grammar("en").unwrap().apply(text)
+    }
+
+    #[test]
+    fn inserts_punctuation_and_layout() {
+        let cases = [
+            ("hello comma world period", "hello, world."),
+            ("is it done question mark", "is it done?"),
+            ("dear team new paragraph thanks", "dear team\n\nThanks"),
+            ("first line new line second", "first line\nSecond"),
+            ("she said open quote hi close quote", "she said \"hi\""),
+            ("see open paren below close paren", "see (below)"),
+            ("state of the art", "state of the art"),
+            ("well hyphen known", "well-known"),
+            ("done period next thing", "done. Next thing"),
+        ];
+        for (spoken, expected) in cases {
+            assert_eq!(english(spoken), expected, "for {:?}", spoken);
+        }
+    }
+
+    #[test]
+    fn tolerates_transcriber_punctuation_and_case() {
+        assert_eq!(english("Hello, comma world. Period."), "Hello, world.");
+        assert_eq!(english("Call me, New line. Bye"), "Call me\nBye");
+    }
+
+    #[test]
+    fn keeps_line_breaks_and_spacing_of_the_transcript() {
+        assert_eq!(english("Hi.\n\nThanks,\nBob"), "Hi.\n\nThanks,\nBob");
+        assert_eq!(english("a\tb comma\nc"), "a\tb,\nc");
+    }
+
+    #[test]
+    fn is_off_by_default() {
+        assert!(!VoiceCommandSettings::default().enabled);
+    }
+
+    #[test]
+    fn scratch_that_removes_the_last_phrase() {
+        assert_eq!(english("buy milk scratch that buy eggs"), "buy eggs");
+        assert_eq!(
+            english("hi comma buy milk scratch that call me"),
+            "hi, call me"
+        );
+        assert_eq!(english("hi comma scratch that hello"), "hello");
+        assert_eq!(english("scratch that"), "");
+    }
+
+    #[test]
+    fn deletes_the_last_sentence() {
+        assert_eq!(
+            english("one period two period delete last sentence three period"),
+            "one. Three."
+        );
+        assert_eq!(
+            english("one period two and delete last sentence three"),
+            "one. Three"
+        );
+    }
+
+    #[test]
+    fn uses_the_grammar_for_the_language() {
+        let settings = VoiceCommandSettings::default();
+        let apply = |language: &str, text: &str| settings.grammar(language).unwrap().apply(text);
+        assert_eq!(apply("de-DE", "Hallo Komma Welt Punkt"), "Hallo, Welt.");
+        assert_eq!(apply("fr", "vraiment point d'interrogation"), "vraiment?");
+        assert_eq!(apply("es", "uno punto y coma dos"), "uno; dos");
+        // Providers report names or ISO 639-3 codes
+        assert_eq!(apply("german", "Hallo Komma Welt"), "Hallo, Welt");
+        assert_eq!(apply("deu", "Hallo Komma Welt"), "Hallo, Welt");
+        assert_eq!(apply("English", "a comma b"), "a, b");
+        // Unknown languages get no commands
+        assert_eq!(settings.grammar("xx"), None);
+        assert_eq!(settings.grammar("italian"), None);
+    }
+
+    #[test]
+    fn maps_languages_to_iso_639_1() {
+        let cases = [
+            ("en-US", "en"),
+            ("de_AT", "de"),
+            ("english", "en"),
+            ("German", "de"),
+            ("deu", "de"),
+            ("fra", "fr"),
+            ("xx", "xx"),
+        ];
+        for (language, expected) in cases {
+            assert_eq!(primary_language(language), expected);
+        }
+    }
+
+    #[test]
+    fn custom_grammar_replaces_builtin() {
+        let mut settings = VoiceCommandSettings::default();
+        settings.grammars.insert(
+            "en".to_string(),
+            CommandGrammar {
+                symbols: vec![SpokenSymbol {
+                    phrase: "smiley".to_string(),
+                    text: ":)".to_string(),
+                    attach: Attach::None,
+                }],
+                ..CommandGrammar::default()
+            },
+        );
+        assert_eq!(
+            settings.grammar("en").unwrap().apply("nice smiley comma"),
+            "nice :) comma"
+        );
+    }
+}